@@ -0,0 +1,244 @@
+use lib_ruby_parser_nodes::{
+    reexports::serde::{Serialize, SerializeStruct, Serializer},
+    MessageFieldType,
+};
+
+/// Diagnostic message, same as `lib_ruby_parser_nodes::Message`
+/// but with an open set of field types
+pub(crate) struct Message {
+    camelcase_name: &'static str,
+    fields: Vec<MessageField>,
+    comment: &'static [&'static str],
+}
+
+pub(crate) struct MessageField {
+    snakecase_name: &'static str,
+    field_type: &'static str,
+    comment: &'static [&'static str],
+}
+
+impl Serialize for Message {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct("Message", 3)?;
+        s.serialize_field("camelcase_name", self.camelcase_name)?;
+        s.serialize_field("fields", &self.fields)?;
+        s.serialize_field("comment", self.comment)?;
+        s.end()
+    }
+}
+
+impl Serialize for MessageField {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct("MessageField", 3)?;
+        s.serialize_field("snakecase_name", self.snakecase_name)?;
+        s.serialize_field("field_type", self.field_type)?;
+        s.serialize_field("comment", self.comment)?;
+        s.end()
+    }
+}
+
+impl From<&lib_ruby_parser_nodes::Message> for Message {
+    fn from(message: &lib_ruby_parser_nodes::Message) -> Self {
+        let fields = message
+            .fields
+            .iter()
+            .map(|field| MessageField {
+                snakecase_name: field.snakecase_name,
+                field_type: match field.field_type {
+                    MessageFieldType::Str => "Str",
+                    MessageFieldType::Byte => "Byte",
+                },
+                comment: field.comment,
+            })
+            .collect();
+
+        Self {
+            camelcase_name: message.camelcase_name,
+            fields,
+            comment: message.comment,
+        }
+    }
+}
+
+/// Returns all messages of `lib-ruby-parser-nodes`
+/// with messages of this crate inserted after the last parser warning
+pub(crate) fn messages() -> Vec<Message> {
+    const LAST_PARSER_WARNING: &str = "DuplicateHashKey";
+
+    let mut messages = vec![];
+    let mut found = false;
+    for message in lib_ruby_parser_nodes::messages().iter() {
+        messages.push(Message::from(*message));
+        if message.camelcase_name == LAST_PARSER_WARNING {
+            messages.extend(parser_warnings());
+            found = true;
+        }
+    }
+    assert!(
+        found,
+        "{} is not found in lib-ruby-parser-nodes messages",
+        LAST_PARSER_WARNING
+    );
+    messages
+}
+
+fn str_field(snakecase_name: &'static str, comment: &'static [&'static str]) -> MessageField {
+    MessageField {
+        snakecase_name,
+        field_type: "Str",
+        comment,
+    }
+}
+
+fn usize_field(snakecase_name: &'static str, comment: &'static [&'static str]) -> MessageField {
+    MessageField {
+        snakecase_name,
+        field_type: "Usize",
+        comment,
+    }
+}
+
+fn parser_warnings() -> Vec<Message> {
+    vec![
+        Message {
+            camelcase_name: "UnusedSuppression",
+            fields: vec![str_field(
+                "name",
+                &["Name of the message that is suppressed"],
+            )],
+            comment: &[
+                "Emitted for suppression comments that silence nothing",
+                "",
+                "```text",
+                "foo # lib-ruby-parser:disable AmbiguousFirstArgument",
+                "```",
+            ],
+        },
+        Message {
+            camelcase_name: "AssignedButUnusedVariable",
+            fields: vec![str_field("name", &["Name of the variable"])],
+            comment: &[
+                "Emitted for code like (only in $VERBOSE mode)",
+                "",
+                "```text",
+                "def m; foo = 1; end",
+                "```",
+            ],
+        },
+        Message {
+            camelcase_name: "UnusedLiteralIgnored",
+            fields: vec![],
+            comment: &[
+                "Emitted for code like (only in $VERBOSE mode)",
+                "",
+                "```text",
+                "def m; 1; 2; end",
+                "```",
+            ],
+        },
+        Message {
+            camelcase_name: "UselessExpressionInVoidContext",
+            fields: vec![str_field(
+                "expr",
+                &["Description of the expression, like `a literal` or `+`"],
+            )],
+            comment: &[
+                "Emitted for code like (only in $VERBOSE mode)",
+                "",
+                "```text",
+                "def m; a + b; c; end",
+                "```",
+            ],
+        },
+        Message {
+            camelcase_name: "LiteralAssignmentInCondition",
+            fields: vec![],
+            comment: &[
+                "Emitted for code like",
+                "",
+                "```text",
+                "if a = 1; end",
+                "```",
+            ],
+        },
+        Message {
+            camelcase_name: "StringLiteralInCondition",
+            fields: vec![],
+            comment: &[
+                "Emitted for code like",
+                "",
+                "```text",
+                "if \"a\"; end",
+                "```",
+            ],
+        },
+        Message {
+            camelcase_name: "RegexLiteralInCondition",
+            fields: vec![],
+            comment: &[
+                "Emitted for code like (with interpolation only in $VERBOSE mode)",
+                "",
+                "```text",
+                "if /a/; end",
+                "```",
+            ],
+        },
+        Message {
+            camelcase_name: "RangeLiteralInCondition",
+            fields: vec![],
+            comment: &[
+                "Emitted for code like (with non-nil/true/false ends only in $VERBOSE mode)",
+                "",
+                "```text",
+                "if 1..2; end",
+                "```",
+            ],
+        },
+        Message {
+            camelcase_name: "DuplicatedWhenClause",
+            fields: vec![usize_field(
+                "line",
+                &["Line number (1-based) of the first clause with the same value"],
+            )],
+            comment: &[
+                "Emitted for code like (only in $VERBOSE mode)",
+                "",
+                "```text",
+                "case foo; when 1; when 1; end",
+                "```",
+            ],
+        },
+        Message {
+            camelcase_name: "UnreachableInClause",
+            fields: vec![],
+            comment: &[
+                "Emitted for code like (only in $VERBOSE mode)",
+                "",
+                "```text",
+                "case foo; in x; in 1; end",
+                "```",
+            ],
+        },
+        Message {
+            camelcase_name: "MismatchedIndentation",
+            fields: vec![
+                str_field("opening", &["Keyword that opens the construct, like `def`"]),
+                usize_field("line", &["Line number (1-based) of the opening keyword"]),
+            ],
+            comment: &[
+                "Emitted for code like (only with `warn_indent` enabled)",
+                "",
+                "```text",
+                "def m",
+                "  end",
+                "```",
+            ],
+        },
+    ]
+}
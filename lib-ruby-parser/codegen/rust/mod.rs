@@ -1,4 +1,7 @@
+mod messages;
+mod pattern_items;
 mod reserved_words;
+mod token_kind;
 
 use lib_ruby_parser_nodes::{
     helpers::{camelcase_to_snakecase, escape_rust_keyword},
@@ -20,12 +23,20 @@ fn no_template_options(template: LiquidTemplate) -> LiquidTemplate {
     template
 }
 
+fn with_messages(template: LiquidTemplate) -> LiquidTemplate {
+    template.with_global("messages", value!(messages::messages()))
+}
+
+fn with_pattern_items(template: LiquidTemplate) -> LiquidTemplate {
+    template.with_global("pattern_items", value!(pattern_items::pattern_items()))
+}
+
 pub(crate) fn codegen() {
     /* Messages */
     render(
         "codegen/rust/messages.liquid",
         "src/error/messages/message_enum.rs",
-        no_template_options,
+        with_messages,
     );
 
     /* Individual node files */
@@ -54,6 +65,9 @@ pub(crate) fn codegen() {
 
     reserved_words::codegen();
 
+    /* Token kinds */
+    token_kind::codegen();
+
     /* Visitor API */
     render(
         "codegen/rust/visitor.liquid",
@@ -65,6 +79,13 @@ pub(crate) fn codegen() {
     render(
         "codegen/rust/finder.liquid",
         "src/traverse/finder/finder_gen.rs",
-        no_template_options,
+        with_pattern_items,
+    );
+
+    /* Node paths */
+    render(
+        "codegen/rust/node_path.liquid",
+        "src/traverse/finder/node_path_gen.rs",
+        with_pattern_items,
     );
 }
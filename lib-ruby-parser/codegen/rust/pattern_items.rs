@@ -0,0 +1,42 @@
+use lib_ruby_parser_nodes::{
+    helpers::{escape_rust_keyword, snakecase_to_camelcase},
+    Node,
+};
+use std::collections::BTreeMap;
+
+/// Maps node name -> field name -> `PatternItem` variant
+/// that leads from the node into the field.
+///
+/// Field names are escaped, i.e. match `field.snakecase_name | escape_rust_keyword`,
+/// so templates can look a variant up as `pattern_items[node.camelcase_name][field_name]`.
+pub(crate) fn pattern_items() -> BTreeMap<String, BTreeMap<String, String>> {
+    lib_ruby_parser_nodes::nodes()
+        .iter()
+        .map(|node| {
+            let fields = node
+                .fields
+                .iter()
+                .map(|field| {
+                    let field_name = escape_rust_keyword(field.snakecase_name);
+                    let variant = pattern_item(node, &field_name);
+                    (field_name, variant)
+                })
+                .collect();
+            (node.camelcase_name.to_string(), fields)
+        })
+        .collect()
+}
+
+fn pattern_item(node: &Node, field_name: &str) -> String {
+    let variant = match (node.wqp_name, field_name) {
+        (_, "statements") => "Stmts",
+        (_, "call") => "MethodCall",
+        (_, "default") => "DefaultValue",
+        (_, "items") => "MlhsItems",
+        ("when", "patterns") | ("undef", "names") => "Args",
+        ("args", "args") | ("procarg0", "args") => "Arglist",
+        ("rescue", "else_") => "ElseBody",
+        _ => return snakecase_to_camelcase(field_name),
+    };
+    variant.to_string()
+}
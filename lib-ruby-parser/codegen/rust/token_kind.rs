@@ -0,0 +1,125 @@
+// Generated by bison from `src/parser/parse.y` before this codegen runs,
+// so it's the grammar that is tracked for changes (`parse.rs` is always newer than the build)
+const PARSE_Y: &str = "src/parser/parse.y";
+const PARSE_RS: &str = "src/parser/parse.rs";
+
+/// Token constant of the `Lexer`
+struct TokenDecl {
+    name: String,
+    alias: Option<String>,
+}
+
+pub(crate) fn codegen() {
+    println!("cargo:rerun-if-changed={}", PARSE_Y);
+
+    let parser = std::fs::read_to_string(PARSE_RS).unwrap();
+    std::fs::write("src/token_kind/token_kind_gen.rs", contents(&parser)).unwrap();
+}
+
+/// Extracts `Lexer` constants that bison emits for every token,
+/// i.e. the same list that `scripts/src/bin/prepare_token_ids.rs` exports:
+///
+/// ```text
+/// /// Token `` "`class'" ``, to be returned by the scanner.
+/// #[allow(non_upper_case_globals, dead_code)]
+/// pub const kCLASS: i32 = 259;
+/// ```
+fn token_decls(parser: &str) -> Vec<TokenDecl> {
+    let mut tokens = vec![];
+    let mut tag: Option<&str> = None;
+
+    for line in parser.lines().map(str::trim) {
+        if let Some(rest) = line.strip_prefix("/// Token ``") {
+            tag = rest.split("``").next().map(str::trim);
+        } else if let (Some(rest), Some(token_tag)) = (line.strip_prefix("pub const "), tag) {
+            let name = rest.split(':').next().unwrap().trim().to_string();
+            let alias = token_tag
+                .strip_prefix('"')
+                .map(|alias| alias.trim_end_matches('"').to_string());
+            tokens.push(TokenDecl { name, alias });
+            tag = None;
+        }
+    }
+
+    assert!(!tokens.is_empty(), "no token constants in {}", PARSE_RS);
+    tokens
+}
+
+fn contents(parser: &str) -> String {
+    let tokens = token_decls(parser);
+
+    format!(
+        "// This file is autogenerated by {generator}
+
+use crate::token_kind::UnknownTokenType;
+use crate::Lexer;
+
+/// An enum of all token types declared in the grammar.
+///
+/// Numeric value of each variant is equal to the corresponding
+/// `Lexer` constant and to `Token::token_type`.
+#[non_exhaustive]
+#[repr(i32)]
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {{
+    {variants}
+}}
+
+impl TokenKind {{
+    /// All token kinds in the order of their declaration
+    pub const ALL: &'static [TokenKind] = &[
+        {all}
+    ];
+
+    /// Returns name of the token kind, e.g. `\"kCLASS\"` for `TokenKind::kCLASS`
+    pub fn name(&self) -> &'static str {{
+        match self {{
+            {names}
+        }}
+    }}
+}}
+
+impl TryFrom<i32> for TokenKind {{
+    type Error = UnknownTokenType;
+
+    fn try_from(token_type: i32) -> Result<Self, Self::Error> {{
+        match token_type {{
+            {try_from}
+            _ => Err(UnknownTokenType {{ token_type }}),
+        }}
+    }}
+}}
+",
+        generator = file!(),
+        variants = join(&tokens, ",\n\n    ", format_variant),
+        all = join(&tokens, ",\n        ", |token| {
+            format!("Self::{name}", name = token.name)
+        }),
+        names = join(&tokens, ",\n            ", |token| {
+            format!("Self::{name} => \"{name}\"", name = token.name)
+        }),
+        try_from = join(&tokens, "\n            ", |token| {
+            format!("Lexer::{name} => Ok(Self::{name}),", name = token.name)
+        }),
+    )
+}
+
+fn format_variant(token: &TokenDecl) -> String {
+    let doc = match &token.alias {
+        Some(alias) if !alias.trim().is_empty() => alias.to_string(),
+        _ => format!("`{}`", token.name),
+    };
+    format!(
+        "/// {doc}\n    {name} = Lexer::{name}",
+        doc = doc,
+        name = token.name
+    )
+}
+
+fn join<F>(tokens: &[TokenDecl], separator: &str, f: F) -> String
+where
+    F: Fn(&TokenDecl) -> String,
+{
+    tokens.iter().map(f).collect::<Vec<_>>().join(separator)
+}
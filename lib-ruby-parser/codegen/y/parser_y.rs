@@ -1,8 +1,22 @@
 extern crate rust_bison_skeleton;
 
+// Debug hooks of the skeleton that are extended with calls to `Tracer`,
+// so that reductions and shifts are reported exactly when the parser performs them
+const TRACE_HOOKS: &[(&str, &str)] = &[
+    (
+        "self.yy_reduce_print(yyn, yystack);",
+        "self.trace_reduce(yyn, yystack);",
+    ),
+    (
+        "self.yy_symbol_print(\"Shifting\", yytoken, &yylval, &yylloc);",
+        "self.trace_shift();",
+    ),
+];
+
 pub(crate) fn codegen() {
     use std::path::Path;
     const PARSE_Y: &str = "src/parser/parse.y";
+    const PARSE_RS: &str = "src/parser/parse.rs";
 
     println!("cargo:rerun-if-changed={}", PARSE_Y);
     println!("Generating parse.rs");
@@ -14,4 +28,30 @@ pub(crate) fn codegen() {
             std::process::exit(1);
         }
     }
+
+    let parse_rs = std::fs::read_to_string(PARSE_RS).unwrap();
+    let mut output = String::with_capacity(parse_rs.len());
+    let mut hooked = vec![0; TRACE_HOOKS.len()];
+    for line in parse_rs.lines() {
+        output.push_str(line);
+        output.push('\n');
+
+        for (idx, (hook, trace_call)) in TRACE_HOOKS.iter().enumerate() {
+            if line.trim() == *hook {
+                let indent = &line[..line.len() - line.trim_start().len()];
+                output.push_str(indent);
+                output.push_str(trace_call);
+                output.push('\n');
+                hooked[idx] += 1;
+            }
+        }
+    }
+    for ((hook, _), count) in TRACE_HOOKS.iter().zip(hooked) {
+        assert_eq!(
+            count, 1,
+            "expected exactly one {:?} in the generated parser",
+            hook
+        );
+    }
+    std::fs::write(PARSE_RS, output).unwrap();
 }
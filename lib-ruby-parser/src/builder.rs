@@ -9,6 +9,7 @@ use alloc_from_pool::{Factory as PoolFactory, PoolValue};
 use crate::error::Diagnostics;
 #[allow(unused_imports)]
 use crate::nodes::*;
+use crate::source::SharedLines;
 use crate::LexState;
 use crate::Loc;
 use crate::{
@@ -53,6 +54,12 @@ pub(crate) enum PKwLabel {
     QuotedLabel((PoolValue<Token>, Vec<Node>, PoolValue<Token>)),
 }
 
+// Operators that MRI reports as "possibly useless" when their result is discarded
+const VOID_CONTEXT_OPERATORS: &[&str] = &[
+    "+", "-", "*", "/", "%", "**", "+@", "-@", "|", "^", "&", "<=>", ">", ">=", "<", "<=", "==",
+    "!=",
+];
+
 #[derive(Debug, Clone)]
 pub(crate) enum ArgsType {
     Args(Option<Box<Node>>),
@@ -68,6 +75,7 @@ pub(crate) struct Builder {
     pattern_variables: VariablesStack,
     pattern_hash_keys: VariablesStack,
     diagnostics: Diagnostics,
+    lines: SharedLines,
     pool_factory: PoolFactory<Token>,
 }
 
@@ -80,6 +88,7 @@ impl Builder {
         pattern_variables: VariablesStack,
         pattern_hash_keys: VariablesStack,
         diagnostics: Diagnostics,
+        lines: SharedLines,
         pool_factory: PoolFactory<Token>,
     ) -> Self {
         Self {
@@ -90,6 +99,7 @@ impl Builder {
             pattern_variables,
             pattern_hash_keys,
             diagnostics,
+            lines,
             pool_factory,
         }
     }
@@ -928,7 +938,11 @@ impl Builder {
                 let do_warn = keys_are_equal(key1, key2);
 
                 if do_warn {
-                    self.warn(DiagnosticMessage::DuplicateHashKey {}, key2.expression());
+                    self.warn_with_related(
+                        DiagnosticMessage::DuplicateHashKey {},
+                        key2.expression(),
+                        vec![(*key1.expression(), "previously defined here".to_string())],
+                    );
                 }
             }
         }
@@ -1079,6 +1093,9 @@ impl Builder {
                     }
 
                     if !self.static_env.is_declared(name_s) {
+                        if name_s == "binding" {
+                            self.static_env.mark_all_used();
+                        }
                         return Box::new(Node::Send(Send {
                             recv: None,
                             method_name: name,
@@ -1092,13 +1109,16 @@ impl Builder {
                         }));
                     }
 
-                    if let Some(current_arg) = self.current_arg_stack.top() {
+                    self.static_env.mark_used(name_s);
+
+                    if let Some((current_arg, current_arg_l)) = self.current_arg_stack.top() {
                         if current_arg == name_s {
-                            self.error(
+                            self.error_with_related(
                                 DiagnosticMessage::CircularArgumentReference {
                                     arg_name: name.clone(),
                                 },
                                 &expression_l,
+                                vec![(current_arg_l, "argument is defined here".to_string())],
                             );
                         }
                     }
@@ -1229,7 +1249,7 @@ impl Builder {
                 self.check_assignment_to_numparam(name_s, &expression_l)?;
                 self.check_reserved_for_numparam(name_s, &expression_l)?;
 
-                self.static_env.declare(name_s);
+                self.static_env.declare_assigned(name_s, &expression_l);
 
                 Node::Lvasgn(Lvasgn {
                     name,
@@ -1415,9 +1435,12 @@ impl Builder {
         let expression_l = join_exprs(&lhs, &rhs);
 
         match &*lhs {
+            Node::Lvasgn(Lvasgn { name, .. }) => {
+                // `a += 1` reads `a`
+                self.static_env.mark_used(name);
+            }
             Node::Gvasgn(_)
             | Node::Ivasgn(_)
-            | Node::Lvasgn(_)
             | Node::Cvasgn(_)
             | Node::Casgn(_)
             | Node::Send(_)
@@ -2030,6 +2053,10 @@ impl Builder {
         let method_name = maybe_value(selector_t);
         let method_name = method_name.unwrap_or_else(|| String::from("call"));
 
+        if receiver.is_none() && method_name == "binding" {
+            self.static_env.mark_all_used();
+        }
+
         self.rewrite_hash_args_to_kwargs(&mut args);
 
         match self.call_type_for_dot(&dot_t) {
@@ -2372,7 +2399,8 @@ impl Builder {
         let result = match self.static_regexp_captures(&receiver) {
             Some(captures) => {
                 for capture in captures {
-                    self.static_env.declare(&capture);
+                    self.static_env
+                        .declare_assigned(&capture, receiver.expression());
                 }
 
                 Node::MatchWithLvasgn(MatchWithLvasgn {
@@ -2446,7 +2474,7 @@ impl Builder {
             let end_l = self.maybe_loc(&end_t);
 
             Ok(Box::new(Node::Send(Send {
-                recv: Some(self.check_condition(receiver)),
+                recv: Some(self.check_condition_in(receiver, false)),
                 method_name: String::from("!"),
                 args: vec![],
                 dot_l: None,
@@ -2648,6 +2676,10 @@ impl Builder {
         let end_l = self.loc(&end_t);
         let expression_l = keyword_l.join(&end_l);
 
+        if self.context.is_verbose() {
+            self.check_duplicate_when(&when_bodies);
+        }
+
         Box::new(Node::Case(Case {
             expr,
             when_bodies,
@@ -3190,6 +3222,10 @@ impl Builder {
         let end_l = self.loc(&end_t);
         let expression_l = self.loc(&case_t).join(&end_l);
 
+        if self.context.is_verbose() {
+            self.check_unreachable_in_patterns(&in_bodies);
+        }
+
         Box::new(Node::CaseMatch(CaseMatch {
             expr,
             in_bodies,
@@ -3289,7 +3325,7 @@ impl Builder {
 
         self.check_lvar_name(name.as_str(), &name_l)?;
         self.check_duplicate_pattern_variable(name.as_str(), &name_l)?;
-        self.static_env.declare(name.as_str());
+        self.static_env.declare_assigned(name.as_str(), &name_l);
 
         Ok(Box::new(Node::MatchVar(MatchVar {
             name,
@@ -3306,7 +3342,7 @@ impl Builder {
 
         self.check_lvar_name(name.as_str(), &name_l)?;
         self.check_duplicate_pattern_variable(name.as_str(), &name_l)?;
-        self.static_env.declare(name.as_str());
+        self.static_env.declare_assigned(name.as_str(), &name_l);
 
         Ok(Box::new(Node::MatchVar(MatchVar {
             name,
@@ -3342,7 +3378,7 @@ impl Builder {
                 self.check_lvar_name(name.as_str(), &name_l)?;
                 self.check_duplicate_pattern_variable(name.as_str(), &name_l)?;
 
-                self.static_env.declare(name.as_str());
+                self.static_env.declare_assigned(name.as_str(), &name_l);
 
                 if let Some(begin_l) = begin_l.as_ref() {
                     let begin_d: i32 = begin_l
@@ -3615,7 +3651,16 @@ impl Builder {
     //
 
     pub(crate) fn check_condition(&self, cond: Box<Node>) -> Box<Node> {
-        let cond = cond;
+        self.check_condition_in(cond, true)
+    }
+
+    // `in_cond` is false for operands of `!` and ends of flip-flops,
+    // MRI doesn't report literals there
+    fn check_condition_in(&self, cond: Box<Node>, in_cond: bool) -> Box<Node> {
+        self.warn_assignment_in_condition(&cond);
+        if in_cond {
+            self.warn_literal_in_condition(&cond);
+        }
 
         match *cond {
             Node::Begin(Begin {
@@ -3626,7 +3671,7 @@ impl Builder {
             }) => {
                 if statements.len() == 1 {
                     let stmt = statements.into_iter().next().unwrap();
-                    let stmt = *self.check_condition(Box::new(stmt));
+                    let stmt = *self.check_condition_in(Box::new(stmt), in_cond);
                     Box::new(Node::Begin(Begin {
                         statements: vec![stmt],
                         begin_l,
@@ -3678,8 +3723,8 @@ impl Builder {
                 operator_l,
                 expression_l,
             }) => Box::new(Node::IFlipFlop(IFlipFlop {
-                left: left.map(|node| self.check_condition(node)),
-                right: right.map(|node| self.check_condition(node)),
+                left: left.map(|node| self.check_condition_in(node, false)),
+                right: right.map(|node| self.check_condition_in(node, false)),
                 operator_l,
                 expression_l,
             })),
@@ -3689,8 +3734,8 @@ impl Builder {
                 operator_l,
                 expression_l,
             }) => Box::new(Node::EFlipFlop(EFlipFlop {
-                left: left.map(|node| self.check_condition(node)),
-                right: right.map(|node| self.check_condition(node)),
+                left: left.map(|node| self.check_condition_in(node, false)),
+                right: right.map(|node| self.check_condition_in(node, false)),
                 operator_l,
                 expression_l,
             })),
@@ -3706,6 +3751,101 @@ impl Builder {
         }
     }
 
+    fn warn_assignment_in_condition(&self, node: &Node) {
+        let value = match node {
+            Node::Lvasgn(Lvasgn { value, .. })
+            | Node::Ivasgn(Ivasgn { value, .. })
+            | Node::Gvasgn(Gvasgn { value, .. })
+            | Node::Cvasgn(Cvasgn { value, .. })
+            | Node::Casgn(Casgn { value, .. }) => value.as_deref(),
+            Node::Masgn(Masgn { rhs, .. }) => Some(&**rhs),
+            _ => None,
+        };
+
+        if value.map(is_static_content).unwrap_or(false) {
+            self.warn(
+                DiagnosticMessage::LiteralAssignmentInCondition {},
+                node.expression(),
+            );
+        }
+    }
+
+    // Like MRI, only dynamic regexes and ranges with literal ends
+    // (other than `nil`, `true` and `false`) are reported in verbose mode
+    fn warn_literal_in_condition(&self, node: &Node) {
+        let verbose = self.context.is_verbose();
+
+        let message = match node {
+            Node::Str(_) | Node::Dstr(_) | Node::Heredoc(_) => {
+                DiagnosticMessage::StringLiteralInCondition {}
+            }
+            Node::Regexp(Regexp { parts, .. }) if verbose || is_static_regexp(parts) => {
+                DiagnosticMessage::RegexLiteralInCondition {}
+            }
+            Node::Irange(Irange { left, right, .. }) | Node::Erange(Erange { left, right, .. }) => {
+                let (left, right) = (range_end_literal(left), range_end_literal(right));
+                if (left == 1 && right == 1) || (verbose && left + right >= 2) {
+                    DiagnosticMessage::RangeLiteralInCondition {}
+                } else {
+                    return;
+                }
+            }
+            _ => return,
+        };
+
+        self.warn(message, node.expression());
+    }
+
+    fn check_duplicate_when(&self, when_bodies: &[Node]) {
+        let mut seen: Vec<&Node> = vec![];
+
+        for when_body in when_bodies {
+            let patterns = match when_body {
+                Node::When(When { patterns, .. }) => patterns,
+                _ => continue,
+            };
+
+            for pattern in patterns {
+                match seen
+                    .iter()
+                    .find(|prev| when_literals_are_equal(prev, pattern))
+                {
+                    Some(prev) => self.warn_with_related(
+                        DiagnosticMessage::DuplicatedWhenClause {
+                            line: self.lines.line_number(prev.expression().begin),
+                        },
+                        pattern.expression(),
+                        vec![(*prev.expression(), "previously defined here".to_string())],
+                    ),
+                    None if is_when_literal(pattern) => seen.push(pattern),
+                    None => {}
+                }
+            }
+        }
+    }
+
+    fn check_unreachable_in_patterns(&self, in_bodies: &[Node]) {
+        let mut irrefutable: Option<Loc> = None;
+
+        for in_body in in_bodies {
+            let (pattern, guard) = match in_body {
+                Node::InPattern(InPattern { pattern, guard, .. }) => (pattern, guard),
+                _ => continue,
+            };
+
+            if let Some(irrefutable_l) = irrefutable {
+                self.warn_with_related(
+                    DiagnosticMessage::UnreachableInClause {},
+                    pattern.expression(),
+                    vec![(irrefutable_l, "this pattern matches any value".to_string())],
+                );
+            } else if guard.is_none() && matches!(**pattern, Node::MatchVar(_)) {
+                // `in x` and `in _` without a guard match any value
+                irrefutable = Some(*pattern.expression());
+            }
+        }
+    }
+
     pub(crate) fn check_duplicate_args<'a>(
         &self,
         args: &'a [Node],
@@ -3816,9 +3956,13 @@ impl Builder {
                     None => return,
                 };
                 if self.arg_name_collides(this_name, that_name) {
-                    self.error(
+                    self.error_with_related(
                         DiagnosticMessage::DuplicatedArgumentName {},
                         self.arg_name_loc(this_arg),
+                        vec![(
+                            *self.arg_name_loc(*that_arg),
+                            "previously defined here".to_string(),
+                        )],
                     )
                 }
             }
@@ -3907,22 +4051,30 @@ impl Builder {
             return Ok(());
         }
 
-        if self.pattern_variables.is_declared(name) {
-            self.error(DiagnosticMessage::DuplicateVariableName {}, loc);
+        if let Some(first_l) = self.pattern_variables.declared_at(name) {
+            self.error_with_related(
+                DiagnosticMessage::DuplicateVariableName {},
+                loc,
+                vec![(first_l, "previously defined here".to_string())],
+            );
             return Err(());
         }
 
-        self.pattern_variables.declare(name);
+        self.pattern_variables.declare(name, loc);
         Ok(())
     }
 
     pub(crate) fn check_duplicate_pattern_key(&self, name: &str, loc: &Loc) -> Result<(), ()> {
-        if self.pattern_hash_keys.is_declared(name) {
-            self.error(DiagnosticMessage::DuplicateKeyName {}, loc);
+        if let Some(first_l) = self.pattern_hash_keys.declared_at(name) {
+            self.error_with_related(
+                DiagnosticMessage::DuplicateKeyName {},
+                loc,
+                vec![(first_l, "previously defined here".to_string())],
+            );
             return Err(());
         }
 
-        self.pattern_hash_keys.declare(name);
+        self.pattern_hash_keys.declare(name, loc);
         Ok(())
     }
 
@@ -4100,18 +4252,38 @@ impl Builder {
     }
 
     pub(crate) fn error(&self, message: DiagnosticMessage, loc: &Loc) {
+        self.error_with_related(message, loc, vec![])
+    }
+
+    pub(crate) fn error_with_related(
+        &self,
+        message: DiagnosticMessage,
+        loc: &Loc,
+        related: Vec<(Loc, String)>,
+    ) {
         self.diagnostics.emit(Diagnostic {
             level: ErrorLevel::Error,
             message,
             loc: *loc,
+            related,
         })
     }
 
     pub(crate) fn warn(&self, message: DiagnosticMessage, loc: &Loc) {
+        self.warn_with_related(message, loc, vec![])
+    }
+
+    pub(crate) fn warn_with_related(
+        &self,
+        message: DiagnosticMessage,
+        loc: &Loc,
+        related: Vec<(Loc, String)>,
+    ) {
         self.diagnostics.emit(Diagnostic {
             level: ErrorLevel::Warning,
             message,
             loc: *loc,
+            related,
         })
     }
 
@@ -4182,6 +4354,81 @@ impl Builder {
         }
     }
 
+    // MRI's `-w` warnings about statements whose values are discarded,
+    // the last statement is the value of the whole list and is never reported
+    pub(crate) fn void_stmts(&self, statements: &[Node]) {
+        if let [init @ .., _last] = statements {
+            // MRI's `block_append` drops leading literals of a list
+            // with its own warning, `void_expr` checks everything after them
+            let literals = init
+                .iter()
+                .take_while(|stmt| is_ignored_literal(without_parens(stmt)))
+                .count();
+            for stmt in &init[..literals] {
+                self.warn(
+                    DiagnosticMessage::UnusedLiteralIgnored {},
+                    without_parens(stmt).expression(),
+                );
+            }
+            for stmt in &init[literals..] {
+                self.void_expr(stmt)
+            }
+        }
+    }
+
+    fn void_expr(&self, node: &Node) {
+        let useless = match without_parens(node) {
+            Node::Send(Send {
+                method_name,
+                dot_l: None,
+                selector_l: Some(selector_l),
+                ..
+            }) if VOID_CONTEXT_OPERATORS.contains(&method_name.as_str()) => {
+                self.warn(
+                    DiagnosticMessage::UselessExpressionInVoidContext {
+                        expr: method_name.clone(),
+                    },
+                    selector_l,
+                );
+                return;
+            }
+
+            Node::Lvar(_)
+            | Node::Gvar(_)
+            | Node::Ivar(_)
+            | Node::Cvar(_)
+            | Node::NthRef(_)
+            | Node::BackRef(_) => "a variable",
+            Node::Const(Const { scope: None, .. }) => "a constant",
+            Node::Const(_) => "::",
+            Node::Int(_)
+            | Node::Float(_)
+            | Node::Rational(_)
+            | Node::Complex(_)
+            | Node::Str(_)
+            | Node::Dstr(_)
+            | Node::Sym(_)
+            | Node::Regexp(_) => "a literal",
+            Node::Array(Array { elements, .. }) if elements.is_empty() => "a literal",
+            Node::Self_(_) => "self",
+            Node::Nil(_) => "nil",
+            Node::True(_) => "true",
+            Node::False(_) => "false",
+            Node::Irange(_) => "..",
+            Node::Erange(_) => "...",
+            Node::Defined(_) => "defined?",
+
+            _ => return,
+        };
+
+        self.warn(
+            DiagnosticMessage::UselessExpressionInVoidContext {
+                expr: useless.to_string(),
+            },
+            without_parens(node).expression(),
+        );
+    }
+
     fn rewrite_hash_args_to_kwargs(&self, args: &mut Vec<Node>) {
         let len = args.len();
 
@@ -4284,6 +4531,108 @@ impl Builder {
     }
 }
 
+// `(expr)` with a single statement
+fn without_parens(node: &Node) -> &Node {
+    match node {
+        Node::Begin(Begin {
+            statements,
+            begin_l: Some(_),
+            ..
+        }) if statements.len() == 1 => without_parens(&statements[0]),
+        _ => node,
+    }
+}
+
+// Mirrors node types that MRI's `block_append` drops with "unused literal ignored"
+fn is_ignored_literal(node: &Node) -> bool {
+    match node {
+        Node::Int(_)
+        | Node::Float(_)
+        | Node::Rational(_)
+        | Node::Complex(_)
+        | Node::Str(_)
+        | Node::Sym(_)
+        | Node::Self_(_)
+        | Node::True(_)
+        | Node::False(_)
+        | Node::Nil(_) => true,
+        Node::Regexp(Regexp { parts, .. }) => is_static_regexp(parts),
+        _ => false,
+    }
+}
+
+fn is_static_regexp(parts: &[Node]) -> bool {
+    parts.iter().all(|part| matches!(part, Node::Str(_)))
+}
+
+fn is_when_literal(node: &Node) -> bool {
+    matches!(
+        node,
+        Node::Int(_) | Node::Str(_) | Node::Sym(_) | Node::Nil(_) | Node::True(_) | Node::False(_)
+    )
+}
+
+fn when_literals_are_equal(left: &Node, right: &Node) -> bool {
+    match (left, right) {
+        (Node::Int(Int { value: value1, .. }), Node::Int(Int { value: value2, .. })) => {
+            value1 == value2
+        }
+        (Node::Str(Str { value: value1, .. }), Node::Str(Str { value: value2, .. })) => {
+            value1 == value2
+        }
+        (Node::Sym(Sym { name: name1, .. }), Node::Sym(Sym { name: name2, .. })) => name1 == name2,
+        (Node::Nil(_), Node::Nil(_))
+        | (Node::True(_), Node::True(_))
+        | (Node::False(_), Node::False(_)) => true,
+        _ => false,
+    }
+}
+
+// Mirrors MRI's `is_static_content`, values that make `if a = <value>` a likely typo
+fn is_static_content(node: &Node) -> bool {
+    match node {
+        Node::Int(_)
+        | Node::Float(_)
+        | Node::Rational(_)
+        | Node::Complex(_)
+        | Node::Sym(_)
+        | Node::Str(_)
+        | Node::Nil(_)
+        | Node::True(_)
+        | Node::False(_) => true,
+        Node::Regexp(Regexp { parts, .. }) => is_static_regexp(parts),
+        Node::Array(Array { elements, .. }) => elements.iter().all(is_static_content),
+        Node::Hash(Hash { pairs, .. }) => pairs.iter().all(|pair| match pair {
+            Node::Pair(Pair { key, value, .. }) => {
+                is_static_content(key) && is_static_content(value)
+            }
+            _ => false,
+        }),
+        _ => false,
+    }
+}
+
+// Mirrors MRI's `literal_node`: 2 for literals, 1 for `nil`/`true`/`false` or a missing end,
+// a range is reported when the sum for both its ends is at least 2
+fn range_end_literal(node: &Option<Box<Node>>) -> u8 {
+    match node.as_deref() {
+        None | Some(Node::Nil(_) | Node::True(_) | Node::False(_)) => 1,
+        Some(
+            Node::Int(_)
+            | Node::Float(_)
+            | Node::Rational(_)
+            | Node::Complex(_)
+            | Node::Sym(_)
+            | Node::Dsym(_)
+            | Node::Str(_)
+            | Node::Dstr(_)
+            | Node::Heredoc(_)
+            | Node::Regexp(_),
+        ) => 2,
+        Some(_) => 0,
+    }
+}
+
 pub(crate) fn maybe_node_expr(node: &Option<&Node>) -> Option<Loc> {
     node.map(|node| *node.expression())
 }
@@ -0,0 +1,261 @@
+use crate::{
+    Diagnostic, DiagnosticMessage, ErrorLevel, Lexer, Loc, Parser, ParserOptions, Token, TokenKind,
+};
+
+/// Kind of a construct that is not closed at the end of the input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenConstructKind {
+    /// A construct that is closed by `end`: `class`, `def`, `if`, `do`, etc.
+    /// Contains the opening keyword
+    Keyword(TokenKind),
+
+    /// `(`
+    Paren,
+
+    /// `[`
+    Bracket,
+
+    /// `{` of a hash, a block or a lambda
+    Brace,
+
+    /// `#{` inside a string
+    Interpolation,
+
+    /// String, symbol or backtick literal
+    String,
+
+    /// Regexp literal
+    Regexp,
+
+    /// Word or symbol list (`%w[`, `%i[`, etc)
+    List,
+
+    /// Heredoc without a terminator
+    Heredoc,
+
+    /// `=begin` without `=end`
+    EmbeddedDocument,
+}
+
+/// A construct that is not closed at the end of the input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpenConstruct {
+    /// Kind of the construct
+    pub kind: OpenConstructKind,
+
+    /// Location of the opening token,
+    /// or the location reported by the lexer for unterminated literals
+    pub loc: Loc,
+}
+
+/// Result of `Parser::check_completeness`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Completeness {
+    /// Input has no errors
+    Complete,
+
+    /// Input is a valid prefix of some code, but it needs more code
+    /// to be complete. Contains constructs that are still open,
+    /// from the outermost to the innermost.
+    ///
+    /// The list can be empty when an expression is not finished, like `1 +`
+    Incomplete(Vec<OpenConstruct>),
+
+    /// Input has errors that can't be fixed by adding more code to it.
+    /// Contains all errors reported by the parser.
+    Invalid(Vec<Diagnostic>),
+}
+
+impl Parser {
+    /// Checks whether given input is complete, for REPLs
+    /// that need to decide whether to evaluate the input
+    /// or to wait for more lines.
+    ///
+    /// # Example
+    /// ```
+    /// use lib_ruby_parser::completeness::{Completeness, OpenConstruct, OpenConstructKind};
+    /// use lib_ruby_parser::{Loc, Parser, TokenKind};
+    ///
+    /// assert_eq!(Parser::check_completeness("def foo; 42; end"), Completeness::Complete);
+    ///
+    /// assert_eq!(
+    ///     Parser::check_completeness("def foo\n  bar(1,"),
+    ///     Completeness::Incomplete(vec![
+    ///         OpenConstruct {
+    ///             kind: OpenConstructKind::Keyword(TokenKind::kDEF),
+    ///             loc: Loc { begin: 0, end: 3 },
+    ///         },
+    ///         OpenConstruct {
+    ///             kind: OpenConstructKind::Paren,
+    ///             loc: Loc { begin: 13, end: 14 },
+    ///         },
+    ///     ])
+    /// );
+    ///
+    /// assert!(matches!(Parser::check_completeness("foo)"), Completeness::Invalid(_)));
+    /// ```
+    pub fn check_completeness<TInput>(input: TInput) -> Completeness
+    where
+        TInput: Into<Vec<u8>>,
+    {
+        let options = ParserOptions {
+            record_tokens: true,
+            // the search for an unclosed block calls this function
+            suggest_unclosed_block: false,
+            ..Default::default()
+        };
+        let result = Parser::new(input, options).do_parse();
+
+        let errors = result
+            .diagnostics
+            .into_iter()
+            .filter(|diagnostic| diagnostic.level == ErrorLevel::Error)
+            .collect::<Vec<_>>();
+        if errors.is_empty() {
+            return Completeness::Complete;
+        }
+
+        let eof_loc = result
+            .tokens
+            .iter()
+            .rev()
+            .find(|token| token.token_type == Lexer::END_OF_INPUT)
+            .map(|token| token.loc);
+
+        let is_unexpected_eof = |error: &Diagnostic| {
+            matches!(error.message, DiagnosticMessage::UnexpectedToken { .. })
+                && Some(error.loc) == eof_loc
+        };
+        let is_incomplete =
+            |error: &Diagnostic| is_unexpected_eof(error) || unterminated_literal(error).is_some();
+        if !errors.iter().all(is_incomplete) {
+            return Completeness::Invalid(errors);
+        }
+
+        let mut open = token_pairs(&result.tokens)
+            .into_iter()
+            .filter(|pair| pair.close.is_none())
+            .map(|pair| pair.open)
+            .collect::<Vec<_>>();
+        open.extend(errors.iter().filter_map(|error| {
+            Some(OpenConstruct {
+                kind: unterminated_literal(error)?,
+                loc: error.loc,
+            })
+        }));
+        open.sort_by_key(|construct| construct.loc.begin);
+
+        Completeness::Incomplete(open)
+    }
+}
+
+/// Returns kind of a literal that is reported by the lexer as unterminated
+fn unterminated_literal(error: &Diagnostic) -> Option<OpenConstructKind> {
+    match error.message {
+        DiagnosticMessage::UnterminatedString {}
+        | DiagnosticMessage::UnterminatedQuotedString {} => Some(OpenConstructKind::String),
+        DiagnosticMessage::UnterminatedRegexp {} => Some(OpenConstructKind::Regexp),
+        DiagnosticMessage::UnterminatedList {} => Some(OpenConstructKind::List),
+        DiagnosticMessage::UnterminatedHeredoc { .. }
+        | DiagnosticMessage::UnterminatedHeredocId {} => Some(OpenConstructKind::Heredoc),
+        DiagnosticMessage::EmbeddedDocumentMeetsEof {} => Some(OpenConstructKind::EmbeddedDocument),
+        _ => None,
+    }
+}
+
+/// An opening keyword or bracket and its closing token (if any)
+pub(crate) struct TokenPair {
+    pub(crate) open: OpenConstruct,
+    pub(crate) close: Option<Loc>,
+}
+
+struct StackItem {
+    /// Index in the list of pairs
+    idx: usize,
+
+    /// `true` for `def` until it's known whether it's an endless method
+    is_def_header: bool,
+}
+
+/// Matches opening keywords and brackets with their closing tokens,
+/// returns pairs in the order of opening tokens
+pub(crate) fn token_pairs(tokens: &[Token]) -> Vec<TokenPair> {
+    let mut pairs: Vec<TokenPair> = vec![];
+    let mut stack: Vec<StackItem> = vec![];
+
+    for token in tokens {
+        let kind = match token.kind() {
+            Some(kind) => kind,
+            None => continue,
+        };
+
+        if let Some(top) = stack.last_mut() {
+            if top.is_def_header {
+                match kind {
+                    TokenKind::tEQL => {
+                        // `def foo() = expr` is closed by the end of the expression
+                        let idx = top.idx;
+                        stack.pop();
+                        pairs.remove(idx);
+                        continue;
+                    }
+                    TokenKind::tNL | TokenKind::tSEMI => top.is_def_header = false,
+                    _ => {}
+                }
+            }
+        }
+
+        let opening = match kind {
+            TokenKind::kCLASS
+            | TokenKind::kMODULE
+            | TokenKind::kDEF
+            | TokenKind::kIF
+            | TokenKind::kUNLESS
+            | TokenKind::kWHILE
+            | TokenKind::kUNTIL
+            | TokenKind::kCASE
+            | TokenKind::kFOR
+            | TokenKind::kBEGIN
+            | TokenKind::kDO
+            | TokenKind::kDO_BLOCK
+            | TokenKind::kDO_LAMBDA => OpenConstructKind::Keyword(kind),
+            TokenKind::tLPAREN | TokenKind::tLPAREN_ARG | TokenKind::tLPAREN2 => {
+                OpenConstructKind::Paren
+            }
+            TokenKind::tLBRACK | TokenKind::tLBRACK2 => OpenConstructKind::Bracket,
+            TokenKind::tLBRACE
+            | TokenKind::tLBRACE_ARG
+            | TokenKind::tLCURLY
+            | TokenKind::tLAMBEG => OpenConstructKind::Brace,
+            TokenKind::tSTRING_DBEG => OpenConstructKind::Interpolation,
+            TokenKind::kEND
+            | TokenKind::tRPAREN
+            | TokenKind::tRBRACK
+            | TokenKind::tRCURLY
+            | TokenKind::tSTRING_DEND => {
+                if let Some(top) = stack.pop() {
+                    pairs[top.idx].close = Some(token.loc);
+                }
+                continue;
+            }
+            _ => continue,
+        };
+
+        stack.push(StackItem {
+            idx: pairs.len(),
+            is_def_header: kind == TokenKind::kDEF,
+        });
+        pairs.push(TokenPair {
+            open: OpenConstruct {
+                kind: opening,
+                loc: token.loc,
+            },
+            close: None,
+        });
+    }
+
+    pairs
+}
+
+#[cfg(test)]
+mod tests;
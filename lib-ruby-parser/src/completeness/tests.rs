@@ -0,0 +1,81 @@
+use crate::completeness::{Completeness, OpenConstructKind};
+use crate::{Parser, TokenKind};
+
+fn open_kinds(input: &str) -> Vec<OpenConstructKind> {
+    match Parser::check_completeness(input) {
+        Completeness::Incomplete(open) => {
+            open.into_iter().map(|construct| construct.kind).collect()
+        }
+        other => panic!("expected {:?} to be incomplete, got {:?}", input, other),
+    }
+}
+
+#[test]
+fn test_complete() {
+    for input in [
+        "",
+        "1 + 2",
+        "def foo(a = 1) = a",
+        "class Foo\n  def bar; end\nend\n",
+        "foo do |x|\n  x\nend",
+        "while x do\n  y\nend",
+        "x = <<~HERE\n  text\nHERE\n",
+    ] {
+        assert_eq!(
+            Parser::check_completeness(input),
+            Completeness::Complete,
+            "{:?}",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_incomplete() {
+    assert_eq!(open_kinds("1 +"), vec![]);
+    assert_eq!(
+        open_kinds("class Foo\n  def bar\n"),
+        vec![
+            OpenConstructKind::Keyword(TokenKind::kCLASS),
+            OpenConstructKind::Keyword(TokenKind::kDEF),
+        ]
+    );
+    assert_eq!(
+        open_kinds("foo.each do |x|\n  [x, {"),
+        vec![
+            OpenConstructKind::Keyword(TokenKind::kDO),
+            OpenConstructKind::Bracket,
+            OpenConstructKind::Brace,
+        ]
+    );
+    assert_eq!(open_kinds("foo(1,\n"), vec![OpenConstructKind::Paren]);
+    assert_eq!(
+        open_kinds("def foo(a)\n"),
+        vec![OpenConstructKind::Keyword(TokenKind::kDEF)]
+    );
+    assert_eq!(open_kinds("x = 'foo"), vec![OpenConstructKind::String]);
+    assert_eq!(open_kinds("x = /foo"), vec![OpenConstructKind::Regexp]);
+    assert_eq!(open_kinds("x = %w[foo"), vec![OpenConstructKind::List]);
+    assert_eq!(
+        open_kinds("x = <<~HERE\n  text\n"),
+        vec![OpenConstructKind::Heredoc]
+    );
+    assert_eq!(
+        open_kinds("=begin\ncomment\n"),
+        vec![OpenConstructKind::EmbeddedDocument]
+    );
+}
+
+#[test]
+fn test_invalid() {
+    for input in ["end", "foo)", "def foo\n  1 2\n"] {
+        assert!(
+            matches!(
+                Parser::check_completeness(input),
+                Completeness::Invalid(errors) if !errors.is_empty()
+            ),
+            "{:?}",
+            input
+        );
+    }
+}
@@ -1,9 +1,39 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
-#[derive(Debug, Clone, Eq, PartialEq, Default)]
+use crate::{TraceEvent, Tracer};
+
+#[derive(Debug, Clone, Default)]
 pub(crate) struct SharedContext {
     value: Rc<RefCell<Context>>,
+    tracer: Option<Rc<Tracer>>,
+    verbose: bool,
+}
+
+/// Read-only snapshot of parser context flags
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(C)]
+pub struct ContextFlags {
+    /// Inside `defined?(...)`
+    pub in_defined: bool,
+
+    /// Inside keyword arguments of a method call
+    pub in_kwarg: bool,
+
+    /// Inside arguments of a method definition
+    pub in_argdef: bool,
+
+    /// Inside a method definition
+    pub in_def: bool,
+
+    /// Inside a class or a module body
+    pub in_class: bool,
+
+    /// Inside a lambda
+    pub in_lambda: bool,
+
+    /// Inside a block
+    pub in_block: bool,
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
@@ -21,9 +51,6 @@ macro_rules! context_flag {
             }
 
             pub(crate) fn $setter(&mut self, value: bool) {
-                if cfg!(feature = "debug-parser") {
-                    println!("{}({})", stringify!($setter), value);
-                }
                 if value {
                     self.value |= Self::$upper;
                 } else {
@@ -39,6 +66,14 @@ macro_rules! context_flag {
             }
 
             pub(crate) fn $setter(&mut self, value: bool) {
+                if let Some(tracer) = self.tracer.as_ref() {
+                    if self.$getter() != value {
+                        tracer.call(&TraceEvent::ContextFlagChanged {
+                            flag: stringify!($getter),
+                            value,
+                        });
+                    }
+                }
                 self.value.borrow_mut().$setter(value)
             }
         }
@@ -54,14 +89,26 @@ context_flag!(IN_LAMBDA, in_lambda, set_in_lambda, 1 << 5);
 context_flag!(IN_BLOCK, in_block, set_in_block, 1 << 6);
 
 impl SharedContext {
-    pub(crate) fn new() -> Self {
-        Self::default()
+    pub(crate) fn new(tracer: Option<Rc<Tracer>>, verbose: bool) -> Self {
+        Self {
+            value: Rc::new(RefCell::new(Context::default())),
+            tracer,
+            verbose,
+        }
+    }
+
+    pub(crate) fn is_verbose(&self) -> bool {
+        self.verbose
     }
 
     pub(crate) fn dump(&self) -> Context {
         *self.value.borrow()
     }
 
+    pub(crate) fn flags(&self) -> ContextFlags {
+        self.value.borrow().flags()
+    }
+
     pub(crate) fn is_in_dynamic_block(&self) -> bool {
         self.in_block() || self.in_lambda()
     }
@@ -72,6 +119,18 @@ impl SharedContext {
 }
 
 impl Context {
+    pub(crate) fn flags(&self) -> ContextFlags {
+        ContextFlags {
+            in_defined: self.in_defined(),
+            in_kwarg: self.in_kwarg(),
+            in_argdef: self.in_argdef(),
+            in_def: self.in_def(),
+            in_class: self.in_class(),
+            in_lambda: self.in_lambda(),
+            in_block: self.in_block(),
+        }
+    }
+
     fn is_empty(&self) -> bool {
         if cfg!(debug_assertions) && self.value != 0 {
             println!(
@@ -111,6 +170,14 @@ fn test_context() {
     assert!(!context.in_def());
     assert!(context.in_class());
 
+    assert_eq!(
+        context.flags(),
+        ContextFlags {
+            in_class: true,
+            ..Default::default()
+        }
+    );
+
     context.set_in_class(false);
     assert!(!context.in_def());
     assert!(!context.in_class());
@@ -0,0 +1,174 @@
+use crate::cst::{Cst, CstElement, CstNode, CstToken};
+use crate::nodes::{Heredoc, XHeredoc};
+use crate::source::{scan_trivia, Trivia, TriviaKind};
+use crate::{Lexer, Loc, Node, ParserResult};
+
+/// AST node with all source ranges that are covered by it and its children
+struct Span<'a> {
+    node: &'a Node,
+    covered: Vec<Loc>,
+    children: Vec<Span<'a>>,
+    tokens: Vec<CstToken<'a>>,
+}
+
+impl<'a> Span<'a> {
+    fn new(node: &'a Node) -> Self {
+        let children = node
+            .children()
+            .into_iter()
+            .map(Span::new)
+            .collect::<Vec<_>>();
+
+        let mut covered = vec![*node.expression()];
+        match node {
+            Node::Heredoc(Heredoc {
+                heredoc_body_l,
+                heredoc_end_l,
+                ..
+            })
+            | Node::XHeredoc(XHeredoc {
+                heredoc_body_l,
+                heredoc_end_l,
+                ..
+            }) => {
+                covered.push(*heredoc_body_l);
+                covered.push(*heredoc_end_l);
+            }
+            _ => {}
+        }
+        for child in children.iter() {
+            covered.extend(child.covered.iter().copied());
+        }
+
+        Self {
+            node,
+            covered: merge(covered),
+            children,
+            tokens: vec![],
+        }
+    }
+
+    fn covers(&self, loc: &Loc) -> bool {
+        self.covered
+            .iter()
+            .any(|range| range.begin <= loc.begin && loc.end <= range.end)
+    }
+
+    fn place(&mut self, token: CstToken<'a>) {
+        match self
+            .children
+            .iter_mut()
+            .find(|child| child.covers(&token.token.loc))
+        {
+            Some(child) => child.place(token),
+            None => self.tokens.push(token),
+        }
+    }
+
+    fn begin(&self) -> usize {
+        self.covered.first().map(|loc| loc.begin).unwrap_or(0)
+    }
+
+    fn into_cst_node(self) -> CstNode<'a> {
+        CstNode {
+            node: Some(self.node),
+            children: into_elements(self.children, self.tokens),
+        }
+    }
+}
+
+fn merge(mut ranges: Vec<Loc>) -> Vec<Loc> {
+    ranges.sort_by_key(|loc| loc.begin);
+    let mut result: Vec<Loc> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match result.last_mut() {
+            Some(last) if range.begin <= last.end => last.end = last.end.max(range.end),
+            _ => result.push(range),
+        }
+    }
+    result
+}
+
+fn into_elements<'a>(spans: Vec<Span<'a>>, tokens: Vec<CstToken<'a>>) -> Vec<CstElement<'a>> {
+    let mut elements = spans
+        .into_iter()
+        .map(|span| (span.begin(), CstElement::Node(span.into_cst_node())))
+        .chain(
+            tokens
+                .into_iter()
+                .map(|token| (token.token.loc.begin, CstElement::Token(token))),
+        )
+        .collect::<Vec<_>>();
+    elements.sort_by_key(|(begin, _)| *begin);
+    elements.into_iter().map(|(_, element)| element).collect()
+}
+
+/// Moves trivia that follows `prev` on the same line into its `trailing_trivia`,
+/// returns the rest
+fn attach_trailing(prev: Option<&mut CstToken>, bytes: &[u8], trivia: Vec<Trivia>) -> Vec<Trivia> {
+    let prev = match prev {
+        Some(prev) if prev.token.loc.end > 0 && bytes[prev.token.loc.end - 1] != b'\n' => prev,
+        _ => return trivia,
+    };
+
+    let split_at = trivia
+        .iter()
+        .position(|trivia| trivia.kind == TriviaKind::Newline)
+        .map(|idx| idx + 1)
+        .unwrap_or(trivia.len());
+    let mut trailing = trivia;
+    let leading = trailing.split_off(split_at);
+    prev.trailing_trivia = trailing;
+    leading
+}
+
+pub(crate) fn build(result: &ParserResult) -> Cst<'_> {
+    let input = &result.input;
+    let bytes = input.as_shared_bytes();
+
+    let mut comments = result.comments.clone();
+    comments.sort_by_key(|comment| comment.location.begin);
+
+    let mut tokens = result
+        .tokens
+        .iter()
+        .filter(|token| token.token_type != Lexer::END_OF_INPUT)
+        .collect::<Vec<_>>();
+    tokens.sort_by_key(|token| (token.loc.begin, token.loc.end));
+
+    let mut cst_tokens: Vec<CstToken> = Vec::with_capacity(tokens.len());
+    let mut pos = 0;
+    for token in tokens {
+        let trivia = scan_trivia(input, &comments, pos, token.loc.begin.max(pos));
+        let leading_trivia = attach_trailing(cst_tokens.last_mut(), bytes, trivia);
+        cst_tokens.push(CstToken {
+            token,
+            leading_trivia,
+            trailing_trivia: vec![],
+        });
+        pos = pos.max(token.loc.end);
+    }
+    let trivia = scan_trivia(input, &comments, pos, input.len());
+    let eof_trivia = attach_trailing(cst_tokens.last_mut(), bytes, trivia);
+
+    let mut spans = result
+        .ast
+        .as_deref()
+        .map(|ast| vec![Span::new(ast)])
+        .unwrap_or_default();
+    let mut root_tokens = vec![];
+    for token in cst_tokens {
+        match spans.iter_mut().find(|span| span.covers(&token.token.loc)) {
+            Some(span) => span.place(token),
+            None => root_tokens.push(token),
+        }
+    }
+
+    Cst {
+        root: CstNode {
+            node: None,
+            children: into_elements(spans, root_tokens),
+        },
+        eof_trivia,
+    }
+}
@@ -0,0 +1,130 @@
+mod build;
+
+use crate::source::{DecodedInput, Trivia};
+use crate::{Loc, Node, ParserResult, Token};
+
+/// A token with attached trivia
+#[derive(Debug, Clone, PartialEq)]
+pub struct CstToken<'a> {
+    /// Token that was consumed by the parser
+    pub token: &'a Token,
+
+    /// Trivia between the trailing trivia of the previous token and this token,
+    /// i.e. comments and whitespaces on previous lines and indentation
+    pub leading_trivia: Vec<Trivia>,
+
+    /// Trivia after this token up to (and including) the end of the line
+    pub trailing_trivia: Vec<Trivia>,
+}
+
+/// An element of the concrete syntax tree
+#[derive(Debug, Clone, PartialEq)]
+pub enum CstElement<'a> {
+    /// Node with its own tokens and child nodes
+    Node(CstNode<'a>),
+
+    /// Token that belongs to the parent node
+    Token(CstToken<'a>),
+}
+
+/// A node of the concrete syntax tree
+#[derive(Debug, Clone, PartialEq)]
+pub struct CstNode<'a> {
+    /// AST node, `None` for the root that holds top-level tokens
+    pub node: Option<&'a Node>,
+
+    /// Child nodes and tokens sorted by their location
+    pub children: Vec<CstElement<'a>>,
+}
+
+/// Lossless concrete syntax tree.
+///
+/// Every byte of the `DecodedInput` is covered by at least one token
+/// or trivia piece, so the source can be reconstructed with `Cst::to_bytes`.
+/// Trivia pieces never overlap with each other or with tokens, but tokens
+/// produced by the lexer may overlap, so a byte can belong to more than one token.
+/// Tokens are attached to the deepest AST node that covers them
+/// (heredoc bodies are attached to `Heredoc`/`XHeredoc` nodes).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cst<'a> {
+    /// Root of the tree
+    pub root: CstNode<'a>,
+
+    /// Trivia after the last token (or the whole input if there are no tokens),
+    /// including `__END__` and everything after it
+    pub eof_trivia: Vec<Trivia>,
+}
+
+impl<'a> Cst<'a> {
+    /// Constructs a concrete syntax tree from a given `ParserResult`.
+    ///
+    /// Tokens must be recorded (see `ParserOptions::record_tokens`),
+    /// otherwise the whole input is treated as trivia.
+    ///
+    /// # Example
+    /// ```
+    /// use lib_ruby_parser::cst::Cst;
+    /// use lib_ruby_parser::{Parser, ParserOptions};
+    ///
+    /// let src = b"foo(1, # comment\n    2)\n__END__\ndata";
+    /// let result = Parser::new(src.to_vec(), ParserOptions::default()).do_parse();
+    /// let cst = Cst::new(&result);
+    ///
+    /// assert_eq!(cst.to_bytes(&result.input), src.to_vec());
+    /// ```
+    pub fn new(result: &'a ParserResult) -> Self {
+        build::build(result)
+    }
+
+    /// Returns all tokens in the order of their location
+    pub fn tokens(&self) -> Vec<&CstToken<'a>> {
+        let mut tokens = vec![];
+        self.root.collect_tokens(&mut tokens);
+        tokens.sort_by_key(|token| (token.token.loc.begin, token.token.loc.end));
+        tokens
+    }
+
+    /// Reconstructs the source code from tokens and trivia,
+    /// the result is always equal to `input`
+    pub fn to_bytes(&self, input: &DecodedInput) -> Vec<u8> {
+        let bytes = input.as_shared_bytes();
+        let mut result = vec![];
+        let mut pos = 0;
+        let mut push = |loc: &Loc| {
+            // tokens produced by the lexer can overlap
+            if loc.end > pos {
+                result.extend_from_slice(&bytes[loc.begin.max(pos)..loc.end]);
+                pos = loc.end;
+            }
+        };
+
+        for token in self.tokens() {
+            token
+                .leading_trivia
+                .iter()
+                .for_each(|trivia| push(&trivia.loc));
+            push(&token.token.loc);
+            token
+                .trailing_trivia
+                .iter()
+                .for_each(|trivia| push(&trivia.loc));
+        }
+        self.eof_trivia.iter().for_each(|trivia| push(&trivia.loc));
+
+        result
+    }
+}
+
+impl<'a> CstNode<'a> {
+    fn collect_tokens<'s>(&'s self, tokens: &mut Vec<&'s CstToken<'a>>) {
+        for child in self.children.iter() {
+            match child {
+                CstElement::Node(node) => node.collect_tokens(tokens),
+                CstElement::Token(token) => tokens.push(token),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,117 @@
+use crate::cst::{Cst, CstElement, CstNode};
+use crate::source::TriviaKind;
+use crate::{Parser, ParserOptions, ParserResult};
+
+fn parse(src: &str) -> ParserResult {
+    Parser::new(src, ParserOptions::default()).do_parse()
+}
+
+fn assert_lossless(src: &str) {
+    let result = parse(src);
+    let cst = Cst::new(&result);
+
+    assert_eq!(
+        String::from_utf8(cst.to_bytes(&result.input)).unwrap(),
+        src.to_string()
+    );
+
+    let mut pos = 0;
+    for token in cst.tokens() {
+        let pieces = token
+            .leading_trivia
+            .iter()
+            .map(|trivia| trivia.loc)
+            .chain(std::iter::once(token.token.loc))
+            .chain(token.trailing_trivia.iter().map(|trivia| trivia.loc));
+        for loc in pieces {
+            assert_eq!(loc.begin, pos, "gap or overlap at {} in {:?}", pos, src);
+            pos = loc.end;
+        }
+    }
+    for trivia in cst.eof_trivia.iter() {
+        assert_eq!(trivia.loc.begin, pos);
+        pos = trivia.loc.end;
+    }
+    assert_eq!(pos, src.len());
+}
+
+#[test]
+fn test_lossless() {
+    assert_lossless("");
+    assert_lossless("  # only comment\n");
+    assert_lossless("foo(1,  2) # comment\nbar \\\n  .baz\n");
+    assert_lossless("=begin\ndoc\n=end\nclass A\n  def m; end\nend\n");
+    assert_lossless("foo(<<~HERE, 1)\n  text #{x}\nHERE\nbar\n");
+    assert_lossless("42\n__END__\ndata\n");
+}
+
+#[test]
+fn test_trivia_attachment() {
+    let result = parse("a = 1 # one\n\n  b\n");
+    let cst = Cst::new(&result);
+    let tokens = cst.tokens();
+
+    let one = tokens
+        .iter()
+        .find(|token| token.token.token_value.as_raw() == b"1")
+        .unwrap();
+    assert_eq!(
+        one.trailing_trivia
+            .iter()
+            .map(|trivia| trivia.kind)
+            .collect::<Vec<_>>(),
+        vec![TriviaKind::Whitespace, TriviaKind::Comment]
+    );
+
+    let b = tokens
+        .iter()
+        .find(|token| token.token.token_value.as_raw() == b"b")
+        .unwrap();
+    assert_eq!(
+        b.leading_trivia
+            .iter()
+            .map(|trivia| trivia.kind)
+            .collect::<Vec<_>>(),
+        vec![TriviaKind::Newline, TriviaKind::Whitespace]
+    );
+}
+
+fn node_tokens(node: &CstNode) -> Vec<String> {
+    node.children
+        .iter()
+        .filter_map(|child| match child {
+            CstElement::Token(token) => Some(token.token.to_string_lossy()),
+            CstElement::Node(_) => None,
+        })
+        .collect()
+}
+
+fn find_node<'a, 'b>(node: &'b CstNode<'a>, str_type: &str) -> Option<&'b CstNode<'a>> {
+    if node.node.map(|node| node.str_type()) == Some(str_type) {
+        return Some(node);
+    }
+    node.children.iter().find_map(|child| match child {
+        CstElement::Node(child) => find_node(child, str_type),
+        CstElement::Token(_) => None,
+    })
+}
+
+#[test]
+fn test_tokens_are_attached_to_nodes() {
+    let result = parse("foo(<<~HERE)\n  text\nHERE\n");
+    let cst = Cst::new(&result);
+
+    let send = find_node(&cst.root, "send").unwrap();
+    assert_eq!(node_tokens(send), vec!["foo", "(", ")"]);
+
+    let heredoc = find_node(&cst.root, "str").unwrap();
+    let mut heredoc_tokens = vec![];
+    heredoc.collect_tokens(&mut heredoc_tokens);
+    heredoc_tokens.sort_by_key(|token| token.token.loc.begin);
+    let sources = heredoc_tokens
+        .iter()
+        .map(|token| token.token.loc.source(&result.input).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(sources.first().map(|s| s.as_str()), Some("<<~HERE"));
+    assert_eq!(sources.last().map(|s| s.trim_end()), Some("HERE"));
+}
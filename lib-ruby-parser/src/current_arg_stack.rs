@@ -1,6 +1,8 @@
 use std::cell::RefCell;
 use std::rc::Rc;
-// Stack that holds names of current arguments,
+
+use crate::Loc;
+// Stack that holds names (and locations) of current arguments,
 // i.e. while parsing
 //   def m1(a = (def m2(b = def m3(c = 1); end); end)); end
 //                                   ^
@@ -11,7 +13,7 @@ use std::rc::Rc;
 //
 #[derive(Debug, Clone, Default)]
 pub(crate) struct CurrentArgStack {
-    stack: Rc<RefCell<Vec<Option<String>>>>,
+    stack: Rc<RefCell<Vec<Option<(String, Loc)>>>>,
 }
 
 impl CurrentArgStack {
@@ -25,11 +27,11 @@ impl CurrentArgStack {
         self.stack.borrow().is_empty()
     }
 
-    pub(crate) fn push(&self, value: Option<String>) {
+    pub(crate) fn push(&self, value: Option<(String, Loc)>) {
         self.stack.borrow_mut().push(value)
     }
 
-    pub(crate) fn set(&self, value: Option<String>) {
+    pub(crate) fn set(&self, value: Option<(String, Loc)>) {
         self.pop();
         self.push(value)
     }
@@ -38,7 +40,7 @@ impl CurrentArgStack {
         self.stack.borrow_mut().pop();
     }
 
-    pub(crate) fn top(&self) -> Option<String> {
+    pub(crate) fn top(&self) -> Option<(String, Loc)> {
         match self.stack.borrow().last() {
             Some(Some(value)) => Some(value.clone()),
             _ => None,
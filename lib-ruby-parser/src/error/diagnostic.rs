@@ -1,7 +1,7 @@
 use crate::source::DecodedInput;
 use crate::Loc;
-use crate::{DiagnosticMessage, ErrorLevel};
-use std::cell::RefCell;
+use crate::{DiagnosticAction, DiagnosticHandler, DiagnosticMessage, ErrorLevel};
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
 /// Diagnostic message that comes from the parser when there's an error or warning
@@ -16,6 +16,11 @@ pub struct Diagnostic {
 
     /// Location of the diagnostic
     pub loc: Loc,
+
+    /// Secondary locations related to the diagnostic with a short
+    /// description of each, e.g. location of the first definition
+    /// of a duplicated argument
+    pub related: Vec<(Loc, String)>,
 }
 
 impl Diagnostic {
@@ -31,37 +36,31 @@ impl Diagnostic {
     /// (test.rb):1: foo++
     /// (test.rb):1:      ^
     /// ```
+    ///
+    /// Related locations (if any) are rendered after it as notes:
+    ///
+    /// ```text
+    /// (test.rb):1:9: error: duplicated argument name
+    /// (test.rb):1: def m(a, a); end
+    /// (test.rb):1:          ^
+    /// (test.rb):1:6: note: previously defined here
+    /// (test.rb):1: def m(a, a); end
+    /// (test.rb):1:       ^
+    /// ```
     pub fn render(&self, input: &DecodedInput) -> Option<String> {
-        let (line_no, line_loc) = self.loc.expand_to_line(input)?;
-        let line = line_loc.source(input)?;
-
-        let filename = &input.name;
-        let (_, start_col) = self.loc.begin_line_col(input)?;
-
-        let prefix = format!("{}:{}", filename, line_no + 1);
-        let highlight = format!(
-            "{indent}^{tildes}",
-            indent = " ".repeat(start_col),
-            tildes = if self.loc.size() > 0 {
-                "~".repeat(self.loc.size() - 1)
-            } else {
-                "".to_string()
-            }
-        );
-
-        Some(
-            format!(
-                "{prefix}:{start_col}: {level}: {message}\n{prefix}: {line}\n{prefix}: {highlight}",
-                prefix = prefix,
-                start_col = start_col,
-                level = self.level.to_string(),
-                message = self.message.render(),
-                line = line,
-                highlight = highlight
-            )
-            .trim()
-            .to_string(),
-        )
+        let mut output = render_loc(
+            input,
+            &self.loc,
+            &self.level.to_string(),
+            &self.message.render(),
+        )?;
+
+        for (loc, message) in self.related.iter() {
+            output.push('\n');
+            output.push_str(&render_loc(input, loc, "note", message)?);
+        }
+
+        Some(output)
     }
 
     /// Returns `true` if level of the diagnostic is `Warning`
@@ -75,22 +74,97 @@ impl Diagnostic {
     }
 }
 
+fn render_loc(input: &DecodedInput, loc: &Loc, level: &str, message: &str) -> Option<String> {
+    let (line_no, line_loc) = loc.expand_to_line(input)?;
+    let line = line_loc.source(input)?;
+
+    let filename = &input.name;
+    let (_, start_col) = loc.begin_line_col(input)?;
+
+    let prefix = format!("{}:{}", filename, line_no + 1);
+    let highlight = format!(
+        "{indent}^{tildes}",
+        indent = " ".repeat(start_col),
+        tildes = if loc.size() > 0 {
+            "~".repeat(loc.size() - 1)
+        } else {
+            "".to_string()
+        }
+    );
+
+    Some(
+        format!(
+            "{prefix}:{start_col}: {level}: {message}\n{prefix}: {line}\n{prefix}: {highlight}",
+            prefix = prefix,
+            start_col = start_col,
+            level = level,
+            message = message,
+            line = line,
+            highlight = highlight
+        )
+        .trim()
+        .to_string(),
+    )
+}
+
 #[derive(Debug, Default, Clone)]
 pub(crate) struct Diagnostics {
     list: Rc<RefCell<Vec<Diagnostic>>>,
+    handler: Option<Rc<DiagnosticHandler>>,
+    aborted: Rc<Cell<bool>>,
+    muted: Rc<Cell<bool>>,
 }
 
 impl Diagnostics {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(handler: Option<DiagnosticHandler>) -> Self {
         Self {
             list: Rc::new(RefCell::new(vec![])),
+            handler: handler.map(Rc::new),
+            aborted: Rc::new(Cell::new(false)),
+            muted: Rc::new(Cell::new(false)),
         }
     }
 
     pub(crate) fn emit(&self, diagnostic: Diagnostic) {
+        if self.is_aborted() {
+            // everything after the abort is a consequence of it
+            return;
+        }
+        if self.muted.get() {
+            // diagnostics are replayed from a checkpoint
+            return;
+        }
+        if let Some(handler) = self.handler.as_ref() {
+            if handler.call(&diagnostic) == DiagnosticAction::Stop {
+                self.aborted.set(true);
+            }
+        }
         self.list.borrow_mut().push(diagnostic)
     }
 
+    pub(crate) fn is_aborted(&self) -> bool {
+        self.aborted.get()
+    }
+
+    /// While muted all emitted diagnostics are dropped
+    /// without calling the handler
+    pub(crate) fn set_muted(&self, muted: bool) {
+        self.muted.set(muted)
+    }
+
+    pub(crate) fn count(&self) -> usize {
+        self.list.borrow().len()
+    }
+
+    pub(crate) fn to_vec(&self) -> Vec<Diagnostic> {
+        self.list.borrow().clone()
+    }
+
+    /// Replaces all diagnostics without calling the handler
+    pub(crate) fn restore(&self, list: Vec<Diagnostic>) {
+        *self.list.borrow_mut() = list
+    }
+
     pub(crate) fn take_inner(self) -> Vec<Diagnostic> {
         self.list.replace(vec![])
     }
@@ -106,6 +180,7 @@ fn test_renders() {
         level: ErrorLevel::Warning,
         message: DiagnosticMessage::FractionAfterNumeric {},
         loc: Loc { begin: 8, end: 12 },
+        related: vec![],
     };
 
     assert_eq!(
@@ -119,18 +194,50 @@ fn test_renders() {
     );
 }
 
+#[test]
+fn test_renders_related() {
+    let source = "def m(a, a); end\n";
+    let mut input = crate::source::DecodedInput::named("(test_render)");
+    input.update_bytes(Vec::from(source));
+
+    let error = Diagnostic {
+        level: ErrorLevel::Error,
+        message: DiagnosticMessage::DuplicatedArgumentName {},
+        loc: Loc { begin: 9, end: 10 },
+        related: vec![(
+            Loc { begin: 6, end: 7 },
+            "previously defined here".to_string(),
+        )],
+    };
+
+    assert_eq!(
+        error.render(&input).expect("failed to render diagnostic"),
+        vec![
+            "(test_render):1:9: error: duplicated argument name",
+            "(test_render):1: def m(a, a); end",
+            "(test_render):1:          ^",
+            "(test_render):1:6: note: previously defined here",
+            "(test_render):1: def m(a, a); end",
+            "(test_render):1:       ^",
+        ]
+        .join("\n")
+    );
+}
+
 #[test]
 fn test_predicates() {
     let error = Diagnostic {
         level: ErrorLevel::Error,
         message: DiagnosticMessage::AliasNthRef {},
         loc: Loc { begin: 1, end: 2 },
+        related: vec![],
     };
 
     let warning = Diagnostic {
         level: ErrorLevel::Warning,
         message: DiagnosticMessage::AliasNthRef {},
         loc: Loc { begin: 1, end: 2 },
+        related: vec![],
     };
 
     assert!(error.is_error());
@@ -0,0 +1,40 @@
+use crate::Diagnostic;
+
+/// Enum of what parser should do after emitting a diagnostic
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[repr(C)]
+pub enum DiagnosticAction {
+    /// Means "keep parsing"
+    Continue,
+
+    /// Means "stop parsing as soon as possible",
+    /// `ParserResult` gets marked as `aborted`
+    Stop,
+}
+
+/// Diagnostic handler function
+pub type DiagnosticHandlerFn = dyn Fn(&Diagnostic) -> DiagnosticAction;
+
+/// Diagnostic handler struct, receives every diagnostic
+/// at the moment when it's emitted
+pub struct DiagnosticHandler {
+    f: Box<DiagnosticHandlerFn>,
+}
+
+impl DiagnosticHandler {
+    /// Constructs a handler based on a given function
+    pub fn new(f: Box<DiagnosticHandlerFn>) -> Self {
+        Self { f }
+    }
+
+    pub(crate) fn call(&self, diagnostic: &Diagnostic) -> DiagnosticAction {
+        let f = &*self.f;
+        f(diagnostic)
+    }
+}
+
+impl std::fmt::Debug for DiagnosticHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DiagnosticHandler").finish()
+    }
+}
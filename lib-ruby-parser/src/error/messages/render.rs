@@ -290,6 +290,53 @@ impl DiagnosticMessage {
                 "key is duplicated and overwritten".to_string()
             }
 
+            Self::UnusedSuppression { name } => {
+                format!("unused suppression of `{}'", name)
+            }
+
+            Self::AssignedButUnusedVariable { name } => {
+                format!("assigned but unused variable - {}", name)
+            }
+
+            Self::UnusedLiteralIgnored {} => {
+                "unused literal ignored".to_string()
+            }
+
+            Self::UselessExpressionInVoidContext { expr } => {
+                format!("possibly useless use of {} in void context", expr)
+            }
+
+            Self::LiteralAssignmentInCondition {} => {
+                "found `= literal' in conditional, should be ==".to_string()
+            }
+
+            Self::StringLiteralInCondition {} => {
+                "string literal in condition".to_string()
+            }
+
+            Self::RegexLiteralInCondition {} => {
+                "regex literal in condition".to_string()
+            }
+
+            Self::RangeLiteralInCondition {} => {
+                "range literal in condition".to_string()
+            }
+
+            Self::DuplicatedWhenClause { line } => {
+                format!("duplicated `when' clause with line {} is ignored", line)
+            }
+
+            Self::UnreachableInClause {} => {
+                "unreachable `in' clause".to_string()
+            }
+
+            Self::MismatchedIndentation { opening, line } => {
+                format!(
+                    "mismatched indentations at 'end' with '{}' at {}",
+                    opening, line
+                )
+            }
+
             // Builder errors
             Self::CircularArgumentReference { arg_name } => {
                 format!("circular argument reference - {}", arg_name)
@@ -890,6 +937,96 @@ mod tests {
         );
     }
     #[test]
+    fn test_render_UnusedSuppression() {
+        assert_eq!(
+            DiagnosticMessage::UnusedSuppression {
+                name: String::from("AmbiguousFirstArgument")
+            }
+            .render(),
+            "unused suppression of `AmbiguousFirstArgument'",
+        );
+    }
+    #[test]
+    fn test_render_AssignedButUnusedVariable() {
+        assert_eq!(
+            DiagnosticMessage::AssignedButUnusedVariable {
+                name: String::from("foo")
+            }
+            .render(),
+            "assigned but unused variable - foo",
+        );
+    }
+    #[test]
+    fn test_render_UnusedLiteralIgnored() {
+        assert_eq!(
+            DiagnosticMessage::UnusedLiteralIgnored {}.render(),
+            "unused literal ignored",
+        );
+    }
+    #[test]
+    fn test_render_UselessExpressionInVoidContext() {
+        assert_eq!(
+            DiagnosticMessage::UselessExpressionInVoidContext {
+                expr: String::from("==")
+            }
+            .render(),
+            "possibly useless use of == in void context",
+        );
+    }
+    #[test]
+    fn test_render_LiteralAssignmentInCondition() {
+        assert_eq!(
+            DiagnosticMessage::LiteralAssignmentInCondition {}.render(),
+            "found `= literal' in conditional, should be ==",
+        );
+    }
+    #[test]
+    fn test_render_StringLiteralInCondition() {
+        assert_eq!(
+            DiagnosticMessage::StringLiteralInCondition {}.render(),
+            "string literal in condition",
+        );
+    }
+    #[test]
+    fn test_render_RegexLiteralInCondition() {
+        assert_eq!(
+            DiagnosticMessage::RegexLiteralInCondition {}.render(),
+            "regex literal in condition",
+        );
+    }
+    #[test]
+    fn test_render_RangeLiteralInCondition() {
+        assert_eq!(
+            DiagnosticMessage::RangeLiteralInCondition {}.render(),
+            "range literal in condition",
+        );
+    }
+    #[test]
+    fn test_render_DuplicatedWhenClause() {
+        assert_eq!(
+            DiagnosticMessage::DuplicatedWhenClause { line: 2 }.render(),
+            "duplicated `when' clause with line 2 is ignored",
+        );
+    }
+    #[test]
+    fn test_render_UnreachableInClause() {
+        assert_eq!(
+            DiagnosticMessage::UnreachableInClause {}.render(),
+            "unreachable `in' clause",
+        );
+    }
+    #[test]
+    fn test_render_MismatchedIndentation() {
+        assert_eq!(
+            DiagnosticMessage::MismatchedIndentation {
+                opening: String::from("def"),
+                line: 3
+            }
+            .render(),
+            "mismatched indentations at 'end' with 'def' at 3",
+        );
+    }
+    #[test]
     fn test_render_CircularArgumentReference() {
         assert_eq!(
             DiagnosticMessage::CircularArgumentReference {
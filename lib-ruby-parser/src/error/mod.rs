@@ -1,8 +1,11 @@
 pub(crate) mod diagnostic;
+pub(crate) mod diagnostic_handler;
 pub(crate) mod level;
 pub(crate) mod messages;
+pub(crate) mod suppressions;
 
 pub use diagnostic::Diagnostic;
 pub(crate) use diagnostic::Diagnostics;
+pub use diagnostic_handler::{DiagnosticAction, DiagnosticHandler, DiagnosticHandlerFn};
 pub use level::ErrorLevel;
 pub use messages::DiagnosticMessage;
@@ -0,0 +1,103 @@
+use crate::source::{DecodedInput, SuppressionComment, SuppressionCommentKind};
+use crate::Loc;
+use crate::{Diagnostic, DiagnosticMessage, ErrorLevel};
+
+#[derive(Debug)]
+struct SuppressedRange<'a> {
+    name: &'a str,
+    name_l: Loc,
+    first_line: usize,
+    last_line: Option<usize>,
+    used: bool,
+}
+
+impl SuppressedRange<'_> {
+    fn covers(&self, line: usize) -> bool {
+        self.first_line <= line && self.last_line.map(|last| line <= last).unwrap_or(true)
+    }
+}
+
+fn line_of(pos: usize, input: &DecodedInput) -> usize {
+    input
+        .line_col_for_pos(pos)
+        .map(|(line, _)| line)
+        .unwrap_or(0)
+}
+
+/// Removes warnings that are silenced by suppression comments
+/// and appends a warning for every suppression that silenced nothing.
+///
+/// Errors can't be suppressed.
+pub(crate) fn apply_suppressions(
+    diagnostics: Vec<Diagnostic>,
+    comments: &[SuppressionComment],
+    input: &DecodedInput,
+) -> Vec<Diagnostic> {
+    if comments.is_empty() {
+        return diagnostics;
+    }
+
+    let mut ranges: Vec<SuppressedRange> = vec![];
+
+    for comment in comments {
+        let line = line_of(comment.name_l.begin, input);
+
+        match comment.kind {
+            SuppressionCommentKind::Disable => ranges.push(SuppressedRange {
+                name: &comment.name,
+                name_l: comment.name_l,
+                first_line: line,
+                last_line: None,
+                used: false,
+            }),
+            SuppressionCommentKind::DisableLine => ranges.push(SuppressedRange {
+                name: &comment.name,
+                name_l: comment.name_l,
+                first_line: line,
+                last_line: Some(line),
+                used: false,
+            }),
+            SuppressionCommentKind::Enable => {
+                if let Some(range) = ranges
+                    .iter_mut()
+                    .rev()
+                    .find(|range| range.name == comment.name && range.last_line.is_none())
+                {
+                    range.last_line = Some(line);
+                }
+            }
+        }
+    }
+
+    let mut result = Vec::with_capacity(diagnostics.len());
+
+    for diagnostic in diagnostics {
+        if diagnostic.is_warning() {
+            let name = diagnostic.message.name();
+            let line = line_of(diagnostic.loc.begin, input);
+
+            if let Some(range) = ranges
+                .iter_mut()
+                .find(|range| range.name == name && range.covers(line))
+            {
+                range.used = true;
+                continue;
+            }
+        }
+
+        result.push(diagnostic);
+    }
+
+    for range in ranges.iter().filter(|range| !range.used) {
+        result.push(Diagnostic {
+            level: ErrorLevel::Warning,
+            message: DiagnosticMessage::UnusedSuppression {
+                name: range.name.to_string(),
+            },
+            loc: range.name_l,
+            related: vec![],
+        })
+    }
+
+    result
+}
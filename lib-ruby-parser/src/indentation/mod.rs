@@ -0,0 +1,257 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::completeness::token_pairs;
+use crate::nodes::*;
+use crate::source::{line_of, SourceLine};
+use crate::{Lexer, Loc, Node, ParserResult, TokenKind};
+
+/// An opening keyword or bracket (`def`, `class`, `do`, `{`, `(`, `begin`, `if`, etc)
+/// and its closing `end` or bracket
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pair {
+    /// Location of the opening token
+    pub open: Loc,
+
+    /// Location of the closing token, `None` if the input ends before it
+    pub close: Option<Loc>,
+}
+
+/// Returns all pairs of opening and closing keywords and brackets
+/// sorted by the location of the opening token.
+///
+/// Locations are taken from the AST where it's available,
+/// pairs that are not in the AST (like string interpolation or
+/// unclosed blocks of a partially typed input) are taken from `tokens`,
+/// so they must be recorded (see `ParserOptions::record_tokens`).
+///
+/// # Example
+/// ```
+/// use lib_ruby_parser::indentation::{pairs, Pair};
+/// use lib_ruby_parser::{Loc, Parser, ParserOptions};
+///
+/// let result = Parser::new("def foo\n  bar {", ParserOptions::default()).do_parse();
+/// assert_eq!(
+///     pairs(&result),
+///     vec![
+///         Pair { open: Loc { begin: 0, end: 3 }, close: None },
+///         Pair { open: Loc { begin: 14, end: 15 }, close: None },
+///     ]
+/// );
+/// ```
+pub fn pairs(result: &ParserResult) -> Vec<Pair> {
+    let mut pairs = vec![];
+    if let Some(ast) = result.ast.as_deref() {
+        collect_ast_pairs(ast, &mut pairs);
+    }
+
+    let known = pairs
+        .iter()
+        .map(|pair| pair.open.begin)
+        .collect::<HashSet<_>>();
+    pairs.extend(
+        token_pairs(&result.tokens)
+            .into_iter()
+            .filter(|pair| !known.contains(&pair.open.loc.begin))
+            .map(|pair| Pair {
+                open: pair.open.loc,
+                close: pair.close,
+            }),
+    );
+
+    pairs.sort_by_key(|pair| pair.open.begin);
+    pairs
+}
+
+/// Returns a pair whose opening or closing token contains a given byte offset
+pub fn matching_pair(result: &ParserResult, offset: usize) -> Option<Pair> {
+    let contains = |loc: &Loc| loc.begin <= offset && offset < loc.end;
+
+    pairs(result)
+        .into_iter()
+        .find(|pair| contains(&pair.open) || pair.close.as_ref().map_or(false, contains))
+}
+
+/// Returns expected indentation level of each line of the input,
+/// `None` for lines that start inside of a string or heredoc
+/// (their content can't be re-indented).
+///
+/// A line is indented once for every preceding line
+/// that opens a pair which is still not closed at the beginning of the line
+/// (so `foo([{` adds only one level), a line that starts with
+/// a closing token is indented like the line with its opening token.
+/// Lines starting with `else`, `elsif`, `when`, `in`, `rescue` or `ensure`
+/// are indented one level less.
+///
+/// # Example
+/// ```
+/// use lib_ruby_parser::indentation::indent_levels;
+/// use lib_ruby_parser::{Parser, ParserOptions};
+///
+/// let src = "if a\nfoo(1,\n2)\nelse\nbar\nend";
+/// let result = Parser::new(src, ParserOptions::default()).do_parse();
+/// assert_eq!(
+///     indent_levels(&result),
+///     vec![Some(0), Some(1), Some(2), Some(0), Some(1), Some(0)]
+/// );
+/// ```
+pub fn indent_levels(result: &ParserResult) -> Vec<Option<usize>> {
+    let lines = &result.input.lines;
+    let bytes = result.input.as_shared_bytes();
+    let first_chars = lines
+        .iter()
+        .map(|line| {
+            (line.start..line.end)
+                .find(|pos| !bytes[*pos].is_ascii_whitespace())
+                .unwrap_or(line.end)
+        })
+        .collect::<Vec<_>>();
+
+    // for each line that opens pairs: the line where the last of them is closed
+    // and whether this line starts with a closing token of one of them
+    let mut closed_at: HashMap<usize, (usize, bool)> = HashMap::new();
+    for pair in pairs(result) {
+        let open_line = line_of(lines, pair.open.begin);
+        let (close_line, at_line_start) = match pair.close {
+            Some(close) => {
+                let close_line = line_of(lines, close.begin);
+                (close_line, close.begin <= first_chars[close_line])
+            }
+            None => (lines.len(), true),
+        };
+        let entry = closed_at.entry(open_line).or_insert((0, false));
+        if close_line > entry.0 {
+            *entry = (close_line, at_line_start);
+        } else if close_line == entry.0 {
+            entry.1 |= at_line_start;
+        }
+    }
+
+    let mut deltas = vec![0_isize; lines.len() + 1];
+    for (open_line, (close_line, at_line_start)) in closed_at {
+        // a line that starts with a closing token is not indented
+        let until = if at_line_start {
+            close_line
+        } else {
+            close_line + 1
+        };
+        if open_line + 1 < until {
+            deltas[open_line + 1] += 1;
+            deltas[until] -= 1;
+        }
+    }
+
+    let mut inside_literal = vec![false; lines.len()];
+    let mut clause_starts = HashSet::new();
+    for token in result.tokens.iter() {
+        if token.token_type == Lexer::tSTRING_CONTENT || token.token_type == Lexer::tSTRING_END {
+            let first_line = line_of(lines, token.loc.begin);
+            for (line_idx, line) in lines.iter().enumerate().skip(first_line) {
+                if line.start >= token.loc.end {
+                    break;
+                }
+                if line.start >= token.loc.begin {
+                    inside_literal[line_idx] = true;
+                }
+            }
+        }
+        if is_clause_keyword(token.kind()) {
+            clause_starts.insert(token.loc.begin);
+        }
+    }
+
+    let mut level = 0;
+    (0..lines.len())
+        .map(|line_idx| {
+            level += deltas[line_idx];
+            if inside_literal[line_idx] {
+                None
+            } else if first_chars[line_idx] < lines[line_idx].end
+                && clause_starts.contains(&first_chars[line_idx])
+            {
+                Some((level as usize).saturating_sub(1))
+            } else {
+                Some(level as usize)
+            }
+        })
+        .collect()
+}
+
+/// Returns `true` for keywords that continue a construct
+/// and so are indented like its opening keyword
+fn is_clause_keyword(kind: Option<TokenKind>) -> bool {
+    matches!(
+        kind,
+        Some(
+            TokenKind::kELSE
+                | TokenKind::kELSIF
+                | TokenKind::kWHEN
+                | TokenKind::kIN
+                | TokenKind::kRESCUE
+                | TokenKind::kENSURE
+        )
+    )
+}
+
+fn collect_ast_pairs(node: &Node, pairs: &mut Vec<Pair>) {
+    let (open, close) = match node {
+        Node::Def(Def {
+            keyword_l, end_l, ..
+        })
+        | Node::Defs(Defs {
+            keyword_l, end_l, ..
+        })
+        | Node::If(If {
+            keyword_l, end_l, ..
+        })
+        | Node::While(While {
+            keyword_l, end_l, ..
+        })
+        | Node::Until(Until {
+            keyword_l, end_l, ..
+        }) => (Some(*keyword_l), *end_l),
+        Node::Class(Class {
+            keyword_l, end_l, ..
+        })
+        | Node::SClass(SClass {
+            keyword_l, end_l, ..
+        })
+        | Node::Module(Module {
+            keyword_l, end_l, ..
+        })
+        | Node::For(For {
+            keyword_l, end_l, ..
+        })
+        | Node::Case(Case {
+            keyword_l, end_l, ..
+        })
+        | Node::CaseMatch(CaseMatch {
+            keyword_l, end_l, ..
+        }) => (Some(*keyword_l), Some(*end_l)),
+        Node::Block(Block { begin_l, end_l, .. })
+        | Node::Numblock(Numblock { begin_l, end_l, .. })
+        | Node::Index(Index { begin_l, end_l, .. }) => (Some(*begin_l), Some(*end_l)),
+        Node::KwBegin(KwBegin { begin_l, end_l, .. })
+        | Node::Begin(Begin { begin_l, end_l, .. })
+        | Node::Array(Array { begin_l, end_l, .. })
+        | Node::Hash(Hash { begin_l, end_l, .. })
+        | Node::Args(Args { begin_l, end_l, .. })
+        | Node::Send(Send { begin_l, end_l, .. })
+        | Node::CSend(CSend { begin_l, end_l, .. }) => (*begin_l, *end_l),
+        _ => (None, None),
+    };
+
+    // `elsif` and endless methods have no closing token
+    if let (Some(open), Some(close)) = (open, close) {
+        pairs.push(Pair {
+            open,
+            close: Some(close),
+        });
+    }
+
+    for child in node.children() {
+        collect_ast_pairs(child, pairs);
+    }
+}
+
+#[cfg(test)]
+mod tests;
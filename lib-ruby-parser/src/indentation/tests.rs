@@ -0,0 +1,122 @@
+use crate::indentation::{indent_levels, matching_pair, pairs, Pair};
+use crate::{Loc, Parser, ParserOptions, ParserResult};
+
+fn parse(src: &str) -> ParserResult {
+    Parser::new(src, ParserOptions::default()).do_parse()
+}
+
+fn loc(begin: usize, end: usize) -> Loc {
+    Loc { begin, end }
+}
+
+#[test]
+fn test_pairs_from_ast() {
+    let result = parse("class A\n  def foo(a)\n    a.each do |x|\n      [x]\n    end\n  end\nend");
+
+    assert_eq!(
+        pairs(&result),
+        vec![
+            // class ... end
+            Pair {
+                open: loc(0, 5),
+                close: Some(loc(63, 66)),
+            },
+            // def ... end
+            Pair {
+                open: loc(10, 13),
+                close: Some(loc(59, 62)),
+            },
+            // (a)
+            Pair {
+                open: loc(17, 18),
+                close: Some(loc(19, 20)),
+            },
+            // do ... end
+            Pair {
+                open: loc(32, 34),
+                close: Some(loc(53, 56)),
+            },
+            // |x|
+            Pair {
+                open: loc(35, 36),
+                close: Some(loc(37, 38)),
+            },
+            // [x]
+            Pair {
+                open: loc(45, 46),
+                close: Some(loc(47, 48)),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_pairs_of_partial_input() {
+    let result = parse("foo(1, \"#{bar}\") do\n  if x\n");
+
+    assert_eq!(
+        pairs(&result),
+        vec![
+            Pair {
+                open: loc(3, 4),
+                close: Some(loc(15, 16)),
+            },
+            Pair {
+                open: loc(8, 10),
+                close: Some(loc(13, 14)),
+            },
+            Pair {
+                open: loc(17, 19),
+                close: None,
+            },
+            Pair {
+                open: loc(22, 24),
+                close: None,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_matching_pair() {
+    let result = parse("begin\n  foo\nend");
+
+    let pair = Pair {
+        open: loc(0, 5),
+        close: Some(loc(12, 15)),
+    };
+    assert_eq!(matching_pair(&result, 2), Some(pair));
+    assert_eq!(matching_pair(&result, 13), Some(pair));
+    assert_eq!(matching_pair(&result, 9), None);
+}
+
+#[test]
+fn test_indent_levels() {
+    let result =
+        parse("def foo\nx = <<~HERE\n  text\nHERE\ncase x\nwhen 1\n[{\na: 1\n}]\nend\nend\n");
+
+    assert_eq!(
+        indent_levels(&result),
+        vec![
+            Some(0),
+            Some(1),
+            None,
+            None,
+            Some(1),
+            Some(1),
+            Some(2),
+            Some(3),
+            Some(2),
+            Some(1),
+            Some(0),
+            Some(0),
+        ]
+    );
+}
+
+#[test]
+fn test_indent_levels_of_partial_input() {
+    let result = parse("foo do\nbar(\n");
+
+    assert_eq!(indent_levels(&result), vec![Some(0), Some(1), Some(2)]);
+}
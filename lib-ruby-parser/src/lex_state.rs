@@ -8,6 +8,119 @@ pub struct LexState {
 }
 
 impl LexState {
+    /// `EXPR_BEG` flag, see `lex_states::EXPR_BEG`
+    pub const EXPR_BEG: LexState = LexState::from_bits(EXPR_BEG);
+    /// `EXPR_END` flag, see `lex_states::EXPR_END`
+    pub const EXPR_END: LexState = LexState::from_bits(EXPR_END);
+    /// `EXPR_ENDARG` flag, see `lex_states::EXPR_ENDARG`
+    pub const EXPR_ENDARG: LexState = LexState::from_bits(EXPR_ENDARG);
+    /// `EXPR_ENDFN` flag, see `lex_states::EXPR_ENDFN`
+    pub const EXPR_ENDFN: LexState = LexState::from_bits(EXPR_ENDFN);
+    /// `EXPR_ARG` flag, see `lex_states::EXPR_ARG`
+    pub const EXPR_ARG: LexState = LexState::from_bits(EXPR_ARG);
+    /// `EXPR_CMDARG` flag, see `lex_states::EXPR_CMDARG`
+    pub const EXPR_CMDARG: LexState = LexState::from_bits(EXPR_CMDARG);
+    /// `EXPR_MID` flag, see `lex_states::EXPR_MID`
+    pub const EXPR_MID: LexState = LexState::from_bits(EXPR_MID);
+    /// `EXPR_FNAME` flag, see `lex_states::EXPR_FNAME`
+    pub const EXPR_FNAME: LexState = LexState::from_bits(EXPR_FNAME);
+    /// `EXPR_DOT` flag, see `lex_states::EXPR_DOT`
+    pub const EXPR_DOT: LexState = LexState::from_bits(EXPR_DOT);
+    /// `EXPR_CLASS` flag, see `lex_states::EXPR_CLASS`
+    pub const EXPR_CLASS: LexState = LexState::from_bits(EXPR_CLASS);
+    /// `EXPR_LABEL` flag, see `lex_states::EXPR_LABEL`
+    pub const EXPR_LABEL: LexState = LexState::from_bits(EXPR_LABEL);
+    /// `EXPR_LABELED` flag, see `lex_states::EXPR_LABELED`
+    pub const EXPR_LABELED: LexState = LexState::from_bits(EXPR_LABELED);
+    /// `EXPR_FITEM` flag, see `lex_states::EXPR_FITEM`
+    pub const EXPR_FITEM: LexState = LexState::from_bits(EXPR_FITEM);
+    /// `EXPR_VALUE` state, an alias for `EXPR_BEG`
+    pub const EXPR_VALUE: LexState = LexState::from_bits(EXPR_VALUE);
+    /// `EXPR_BEG_ANY` state, `EXPR_BEG|EXPR_MID|EXPR_CLASS`
+    pub const EXPR_BEG_ANY: LexState = LexState::from_bits(EXPR_BEG_ANY);
+    /// `EXPR_ARG_ANY` state, `EXPR_ARG|EXPR_CMDARG`
+    pub const EXPR_ARG_ANY: LexState = LexState::from_bits(EXPR_ARG_ANY);
+    /// `EXPR_END_ANY` state, `EXPR_END|EXPR_ENDARG|EXPR_ENDFN`
+    pub const EXPR_END_ANY: LexState = LexState::from_bits(EXPR_END_ANY);
+    /// `EXPR_NONE` state, no flags are set
+    pub const EXPR_NONE: LexState = LexState::from_bits(EXPR_NONE);
+
+    /// All single flags with their names, in the order of their bits
+    pub const FLAGS: &'static [(&'static str, LexState)] = &[
+        ("EXPR_BEG", Self::EXPR_BEG),
+        ("EXPR_END", Self::EXPR_END),
+        ("EXPR_ENDARG", Self::EXPR_ENDARG),
+        ("EXPR_ENDFN", Self::EXPR_ENDFN),
+        ("EXPR_ARG", Self::EXPR_ARG),
+        ("EXPR_CMDARG", Self::EXPR_CMDARG),
+        ("EXPR_MID", Self::EXPR_MID),
+        ("EXPR_FNAME", Self::EXPR_FNAME),
+        ("EXPR_DOT", Self::EXPR_DOT),
+        ("EXPR_CLASS", Self::EXPR_CLASS),
+        ("EXPR_LABEL", Self::EXPR_LABEL),
+        ("EXPR_LABELED", Self::EXPR_LABELED),
+        ("EXPR_FITEM", Self::EXPR_FITEM),
+    ];
+
+    /// Constructs a state from raw bits (see `lex_states` module)
+    pub const fn from_bits(bits: i32) -> Self {
+        Self { value: bits }
+    }
+
+    /// Returns raw bits of the state
+    pub const fn bits(&self) -> i32 {
+        self.value
+    }
+
+    /// Returns `true` if no flags are set
+    pub const fn is_empty(&self) -> bool {
+        self.value == EXPR_NONE
+    }
+
+    /// Returns `true` if all flags of `other` are set
+    pub const fn contains(&self, other: LexState) -> bool {
+        (self.value & other.value) == other.value
+    }
+
+    /// Returns `true` if at least one flag of `other` is set
+    pub const fn intersects(&self, other: LexState) -> bool {
+        (self.value & other.value) != 0
+    }
+
+    /// Returns a state with flags of both `self` and `other`
+    pub const fn union(self, other: LexState) -> Self {
+        Self::from_bits(self.value | other.value)
+    }
+
+    /// Returns a state with flags that are set in both `self` and `other`
+    pub const fn intersection(self, other: LexState) -> Self {
+        Self::from_bits(self.value & other.value)
+    }
+
+    /// Returns a state with flags of `self` that are not set in `other`
+    pub const fn difference(self, other: LexState) -> Self {
+        Self::from_bits(self.value & !other.value)
+    }
+
+    /// Sets all flags of `other`
+    pub fn insert(&mut self, other: LexState) {
+        self.value |= other.value
+    }
+
+    /// Unsets all flags of `other`
+    pub fn remove(&mut self, other: LexState) {
+        self.value &= !other.value
+    }
+
+    /// Returns names of all flags that are set, e.g. `["EXPR_BEG", "EXPR_LABEL"]`
+    pub fn flag_names(&self) -> Vec<&'static str> {
+        Self::FLAGS
+            .iter()
+            .filter(|(_, flag)| self.intersects(*flag))
+            .map(|(name, _)| *name)
+            .collect()
+    }
+
     pub(crate) fn is(&self, value: i32) -> bool {
         self.value == value
     }
@@ -20,35 +133,97 @@ impl LexState {
         (self.value & states) == states
     }
 
-    /// Sets state to given `value`
-    pub fn set(&mut self, value: i32) {
-        self.value = value
+    /// Sets state to given `value`,
+    /// accepts both `LexState` and raw bits from `lex_states` module
+    pub fn set(&mut self, value: impl Into<LexState>) {
+        self.value = value.into().value
     }
 
     pub(crate) fn get(&self) -> i32 {
         self.value
     }
 
-    pub(crate) fn is_after_operator(&self) -> bool {
+    /// Returns `true` if the lexer is after `def`/`alias`/`undef` (`EXPR_FNAME`)
+    /// or after `.`/`&.` (`EXPR_DOT`), i.e. expects a method name
+    pub fn is_after_operator(&self) -> bool {
         self.is_some(EXPR_FNAME | EXPR_DOT)
     }
-    pub(crate) fn is_end(&self) -> bool {
+
+    /// Returns `true` if the lexer is at the end of an expression (`EXPR_END_ANY`)
+    pub fn is_end(&self) -> bool {
         self.is_some(EXPR_END_ANY)
     }
-    pub(crate) fn is_arg(&self) -> bool {
+
+    /// Returns `true` if the lexer expects a method argument (`EXPR_ARG_ANY`)
+    pub fn is_arg(&self) -> bool {
         self.is_some(EXPR_ARG_ANY)
     }
-    pub(crate) fn is_label_possible(&self, cmd_state: bool) -> bool {
+
+    /// Returns `true` if `foo:` can be parsed as a label.
+    ///
+    /// `cmd_state` is `true` when the lexer is at the beginning of a command.
+    pub fn is_label_possible(&self, cmd_state: bool) -> bool {
         (self.is_some(EXPR_LABEL | EXPR_ENDFN) && !cmd_state) || self.is_arg()
     }
+
     pub(crate) fn is_spacearg(&self, c: MaybeByte, space_seen: bool) -> bool {
         self.is_arg() && space_seen && !c.is_space()
     }
-    pub(crate) fn is_beg(&self) -> bool {
+
+    /// Returns `true` if the lexer is at the beginning of an expression
+    pub fn is_beg(&self) -> bool {
         self.is_some(EXPR_BEG_ANY) || self.is_all(EXPR_ARG | EXPR_LABELED)
     }
 }
 
+impl From<i32> for LexState {
+    fn from(bits: i32) -> Self {
+        Self::from_bits(bits)
+    }
+}
+
+impl From<LexState> for i32 {
+    fn from(state: LexState) -> Self {
+        state.bits()
+    }
+}
+
+impl std::ops::BitOr for LexState {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        self.union(other)
+    }
+}
+
+impl std::ops::BitOrAssign for LexState {
+    fn bitor_assign(&mut self, other: Self) {
+        self.insert(other)
+    }
+}
+
+impl std::ops::BitAnd for LexState {
+    type Output = Self;
+
+    fn bitand(self, other: Self) -> Self {
+        self.intersection(other)
+    }
+}
+
+impl std::ops::BitAndAssign for LexState {
+    fn bitand_assign(&mut self, other: Self) {
+        self.value &= other.value
+    }
+}
+
+impl std::ops::Sub for LexState {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self.difference(other)
+    }
+}
+
 impl Default for LexState {
     fn default() -> Self {
         Self { value: EXPR_BEG }
@@ -115,71 +290,39 @@ use lex_states::*;
 
 impl std::fmt::Debug for LexState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut states: Vec<&'static str> = vec![];
-
-        if self.is_some(EXPR_BEG) {
-            states.push("EXPR_BEG")
-        }
-        if self.is_some(EXPR_END) {
-            states.push("EXPR_END")
-        }
-        if self.is_some(EXPR_ENDARG) {
-            states.push("EXPR_ENDARG")
-        }
-        if self.is_some(EXPR_ENDFN) {
-            states.push("EXPR_ENDFN")
-        }
-        if self.is_some(EXPR_ARG) {
-            states.push("EXPR_ARG")
-        }
-        if self.is_some(EXPR_CMDARG) {
-            states.push("EXPR_CMDARG")
+        if self.is_empty() {
+            return f.write_str("EXPR_NONE");
         }
-        if self.is_some(EXPR_MID) {
-            states.push("EXPR_MID")
-        }
-        if self.is_some(EXPR_FNAME) {
-            states.push("EXPR_FNAME")
-        }
-        if self.is_some(EXPR_DOT) {
-            states.push("EXPR_DOT")
-        }
-        if self.is_some(EXPR_CLASS) {
-            states.push("EXPR_CLASS")
-        }
-        if self.is_some(EXPR_LABEL) {
-            states.push("EXPR_LABEL")
-        }
-        if self.is_some(EXPR_FITEM) {
-            states.push("EXPR_FITEM")
-        }
-        if self.is_some(EXPR_NONE) {
-            states.push("EXPR_NONE")
-        }
-
-        if self.is_some(EXPR_VALUE) {
-            states.push("Also(EXPR_VALUE)")
-        }
-        if self.is_some(EXPR_BEG_ANY) {
-            states.push("Also(EXPR_BEG_ANY)")
-        }
-        if self.is_some(EXPR_END_ANY) {
-            states.push("Also(EXPR_END_ANY)")
-        }
-        if self.is_some(EXPR_END_ANY) {
-            states.push("Also(EXPR_END_ANY)")
-        }
-
-        f.write_str(&states.join("|"))
+        f.write_str(&self.flag_names().join("|"))
     }
 }
 
 #[test]
 fn test_fmt() {
     let mut lex_state = LexState::default();
-    lex_state.set(EXPR_BEG | EXPR_VALUE);
-    assert_eq!(
-        format!("{:?}", lex_state),
-        "EXPR_BEG|Also(EXPR_VALUE)|Also(EXPR_BEG_ANY)"
-    );
+    lex_state.set(EXPR_BEG | EXPR_LABEL);
+    assert_eq!(format!("{:?}", lex_state), "EXPR_BEG|EXPR_LABEL");
+
+    lex_state.set(EXPR_NONE);
+    assert_eq!(format!("{:?}", lex_state), "EXPR_NONE");
+}
+
+#[test]
+fn test_set_operations() {
+    let mut lex_state = LexState::EXPR_ARG | LexState::EXPR_LABELED;
+    assert!(lex_state.contains(LexState::EXPR_ARG));
+    assert!(lex_state.intersects(LexState::EXPR_ARG_ANY));
+    assert!(!lex_state.contains(LexState::EXPR_ARG_ANY));
+    assert!(lex_state.is_beg());
+    assert!(lex_state.is_arg());
+
+    lex_state.remove(LexState::EXPR_LABELED);
+    assert_eq!(lex_state, LexState::EXPR_ARG);
+    assert!(!lex_state.is_beg());
+
+    lex_state.insert(LexState::EXPR_LABEL);
+    assert_eq!(lex_state - LexState::EXPR_ARG, LexState::EXPR_LABEL);
+    assert_eq!(lex_state & LexState::EXPR_LABEL, LexState::EXPR_LABEL);
+    assert_eq!(lex_state.bits(), EXPR_ARG | EXPR_LABEL);
+    assert_eq!(LexState::from(EXPR_ARG | EXPR_LABEL), lex_state);
 }
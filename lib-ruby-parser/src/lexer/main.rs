@@ -1,4 +1,5 @@
 use alloc_from_pool::{Factory as PoolFactory, PoolValue};
+use std::rc::Rc;
 
 use crate::lexer::*;
 use crate::maybe_byte::*;
@@ -6,6 +7,8 @@ use crate::source::buffer::*;
 use crate::source::Comment;
 use crate::source::Decoder;
 use crate::source::MagicComment;
+use crate::source::SharedLines;
+use crate::source::SuppressionComment;
 use crate::str_term::{str_types::*, HeredocEnd, StrTerm, StringLiteral};
 use crate::Loc;
 use crate::SharedContext;
@@ -16,6 +19,8 @@ use crate::TokenBuf;
 use crate::{error::Diagnostics, Bytes};
 use crate::{lex_states::*, LexState};
 use crate::{Diagnostic, DiagnosticMessage, ErrorLevel};
+use crate::{Parser, ParserOptions};
+use crate::{TraceEvent, Tracer};
 
 /// A struct responsible for converting a given input
 /// into a sequence of tokens
@@ -77,13 +82,37 @@ pub struct Lexer {
     pub static_env: StaticEnvironment,
 
     pub(crate) diagnostics: Diagnostics,
+    pub(crate) lines: SharedLines,
     pub(crate) comments: Vec<Comment>,
     pub(crate) magic_comments: Vec<MagicComment>,
+    pub(crate) suppression_comments: Vec<SuppressionComment>,
+    pub(crate) tracer: Option<Rc<Tracer>>,
 
     #[doc(hidden)]
     pub tokens_factory: PoolFactory<Token>,
 }
 
+/// Token types of synthetic trivia tokens, see `ParserOptions::record_trivia`.
+///
+/// These tokens are never passed to the parser.
+#[allow(non_upper_case_globals)]
+impl Lexer {
+    /// Run of whitespace characters
+    pub const tWHITESPACE: i32 = -1;
+    /// Newline that is ignored by the parser
+    pub const tIGNORED_NL: i32 = -2;
+    /// `# comment`
+    pub const tCOMMENT: i32 = -3;
+    /// `=begin`/`=end` block
+    pub const tEMBDOC: i32 = -4;
+    /// Backslash followed by a newline
+    pub const tLINE_CONTINUATION: i32 = -5;
+    /// `__END__` marker and all data after it
+    pub const tEND_OF_DATA: i32 = -6;
+    /// Anything else that is skipped by the lexer
+    pub const tUNKNOWN_TRIVIA: i32 = -7;
+}
+
 impl Lexer {
     pub(crate) const NULL_CHAR: u8 = 0x00;
     pub(crate) const CTRL_D_CHAR: u8 = 0x04;
@@ -112,7 +141,8 @@ impl Lexer {
     /// and so this method on its own can return a wrong sequence
     /// of tokens. It's used internally to test simple inputs.
     ///
-    /// If you need to get tokens better use `ParserResult::tokens` field
+    /// If you need to get tokens better use `Lexer::tokenize`
+    /// or `ParserResult::tokens` field
     pub fn tokenize_until_eof(&mut self) -> Vec<Token> {
         let mut tokens = vec![];
 
@@ -127,6 +157,56 @@ impl Lexer {
         tokens
     }
 
+    /// Tokenizes given input with the lexer driven by the parser,
+    /// so returned tokens are exactly the same as `ParserResult::tokens`
+    /// (including `END_OF_INPUT`), even for context-dependent inputs
+    /// like `foo /x/` or `a ? b : c`.
+    ///
+    /// This is not a cheaper alternative to `Parser::do_parse`:
+    /// grammar actions still run and the builder still constructs every node,
+    /// because they declare local variables and drive error recovery,
+    /// and so they affect the returned tokens.
+    /// Only two things are saved:
+    ///
+    /// 1. the AST of the whole input is never kept, nodes of each top-level
+    ///    statement are dropped right after it's parsed
+    ///    (but a single statement, like a large class, is built in full)
+    /// 2. verbose-only warnings, mismatched indentation warnings
+    ///    and the unclosed block search are skipped, i.e. `options.verbose`,
+    ///    `options.warn_indent` and `options.suggest_unclosed_block` are ignored
+    ///
+    /// Diagnostics are not returned, `options.record_tokens` is ignored.
+    ///
+    /// # Example
+    /// ```
+    /// use lib_ruby_parser::{Lexer, ParserOptions};
+    ///
+    /// // `foo` is a local variable, so `/` is a division, not a regexp
+    /// let tokens = Lexer::tokenize(b"foo = 1; foo /x/ 2".to_vec(), ParserOptions::default());
+    /// let names = tokens.iter().map(|token| token.token_name()).collect::<Vec<_>>();
+    ///
+    /// assert_eq!(
+    ///     names,
+    ///     vec![
+    ///         "tIDENTIFIER", "tEQL", "tINTEGER", "tSEMI",
+    ///         "tIDENTIFIER", "tDIVIDE", "tIDENTIFIER", "tDIVIDE", "tINTEGER",
+    ///         "END_OF_INPUT"
+    ///     ]
+    /// );
+    /// ```
+    pub fn tokenize<TInput>(input: TInput, options: ParserOptions) -> Vec<Token>
+    where
+        TInput: Into<Vec<u8>>,
+    {
+        let options = ParserOptions {
+            verbose: false,
+            warn_indent: false,
+            suggest_unclosed_block: false,
+            ..options
+        };
+        Parser::new(input, options).do_tokenize()
+    }
+
     pub(crate) fn yylex(&mut self) -> PoolValue<Token> {
         let lex_state_before = self.lex_state;
         self.lval = None;
@@ -159,12 +239,15 @@ impl Lexer {
             lex_state_before,
             lex_state_after: self.lex_state,
         });
-        println_if_debug_lexer!(
-            "yylex ({:?}, {:?}, {:?})",
-            token.token_name(),
-            token.token_value,
-            token.loc
-        );
+        if let Some(tracer) = self.tracer.as_ref() {
+            tracer.call(&TraceEvent::TokenLexed { token: &token });
+            if lex_state_before != self.lex_state {
+                tracer.call(&TraceEvent::LexStateChanged {
+                    before: lex_state_before,
+                    after: self.lex_state,
+                });
+            }
+        }
         token
     }
 
@@ -250,6 +333,7 @@ impl Lexer {
                             }
                             Err(_) => return Self::END_OF_INPUT,
                         }
+                        self.suppression_comment(self.buffer.pcur, self.buffer.pend);
                         self.buffer.goto_eol();
                         self.comments
                             .push(Comment::new(self.current_loc(), &self.buffer.input.decoded))
@@ -1097,11 +1181,11 @@ impl Lexer {
     }
 
     pub(crate) fn warn(&mut self, message: DiagnosticMessage, loc: Loc) {
-        println_if_debug_lexer!("WARNING: {}", message.render());
         let diagnostic = Diagnostic {
             level: ErrorLevel::Warning,
             message,
             loc,
+            related: vec![],
         };
         self.diagnostics.emit(diagnostic);
     }
@@ -1130,11 +1214,11 @@ impl Lexer {
     }
 
     pub(crate) fn compile_error(&mut self, message: DiagnosticMessage, loc: Loc) {
-        println_if_debug_lexer!("Compile error: {}", message.render());
         let diagnostic = Diagnostic {
             level: ErrorLevel::Error,
             message,
             loc,
+            related: vec![],
         };
         self.diagnostics.emit(diagnostic);
     }
@@ -1191,11 +1275,11 @@ impl Lexer {
     }
 
     pub(crate) fn yyerror1(&mut self, message: DiagnosticMessage, loc: Loc) {
-        println_if_debug_lexer!("yyerror0: {}", message.render());
         let diagnostic = Diagnostic {
             level: ErrorLevel::Error,
             message,
             loc,
+            related: vec![],
         };
         self.diagnostics.emit(diagnostic);
     }
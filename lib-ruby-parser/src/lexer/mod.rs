@@ -1,12 +1,3 @@
-macro_rules! println_if_debug_lexer {
-    ($fmt_string:expr, $( $arg:expr ),*) => {
-        if cfg!(feature = "debug-lexer") {
-            println!($fmt_string, $( $arg ),*);
-        }
-    };
-}
-pub(crate) use println_if_debug_lexer;
-
 mod main;
 mod parse_atmark;
 mod parse_gvar;
@@ -17,8 +8,11 @@ mod parse_numeric;
 mod parse_percent;
 mod parse_qmark;
 mod parse_string;
+mod snapshot;
 mod tokadd;
 mod yylval;
 
 pub use main::Lexer;
+pub use snapshot::{InputEdit, LexerSnapshot, LineTokens, RelexResult};
+pub(crate) use snapshot::LexerStepState;
 pub(crate) use tokadd::TokAdd;
@@ -1,6 +1,7 @@
 use std::convert::TryInto;
 
 use crate::source::{MagicComment, MagicCommentKind};
+use crate::source::{SuppressionComment, SuppressionCommentKind};
 use crate::DiagnosticMessage;
 use crate::Lexer;
 
@@ -20,6 +21,8 @@ const MAGIC_COMMENTS: &[MagicCommentData] = &[
     ("warn_indent", MagicCommentKind::WarnIndent),
 ];
 
+const SUPPRESSION_PREFIX: &[u8] = b"lib-ruby-parser:";
+
 impl Lexer {
     pub(crate) fn comment_at_top(&self) -> bool {
         let mut ptr = self.buffer.pbeg;
@@ -340,7 +343,7 @@ impl Lexer {
                             }
                         };
                         match self.buffer.set_encoding(&encoding) {
-                            Ok(_) => {}
+                            Ok(_) => self.lines.update(&self.buffer.input.decoded.lines),
                             Err(err) => {
                                 self.yyerror1(
                                     DiagnosticMessage::EncodingError {
@@ -368,4 +371,73 @@ impl Lexer {
 
         Ok(true)
     }
+
+    fn comment_is_trailing(&self) -> bool {
+        let mut ptr = self.buffer.pbeg;
+        let ptr_end = self.buffer.pcur - 1;
+        while ptr < ptr_end {
+            if !self.char_at(ptr).is_space() {
+                return true;
+            }
+            ptr += 1;
+        }
+        false
+    }
+
+    pub(crate) fn suppression_comment(&mut self, mut str_: usize, send: usize) {
+        while str_ < send && self.char_at(str_).is_space() {
+            str_ += 1;
+        }
+
+        let prefix_end = str_ + SUPPRESSION_PREFIX.len();
+        if self.buffer.substr_at(str_, prefix_end) != Some(SUPPRESSION_PREFIX) {
+            return;
+        }
+        str_ = prefix_end;
+
+        let kind = if self.buffer.substr_at(str_, str_ + 7) == Some(b"disable") {
+            str_ += 7;
+            if self.comment_is_trailing() {
+                SuppressionCommentKind::DisableLine
+            } else {
+                SuppressionCommentKind::Disable
+            }
+        } else if self.buffer.substr_at(str_, str_ + 6) == Some(b"enable") {
+            str_ += 6;
+            SuppressionCommentKind::Enable
+        } else {
+            return;
+        };
+
+        if !self.char_at(str_).is_space() {
+            return;
+        }
+
+        loop {
+            while str_ < send && (self.char_at(str_).is_space() || self.char_at(str_) == b',') {
+                str_ += 1;
+            }
+
+            let beg = str_;
+            while str_ < send && (self.char_at(str_).is_alnum() || self.char_at(str_) == b'_') {
+                str_ += 1;
+            }
+            if beg == str_ {
+                break;
+            }
+
+            let name = String::from_utf8_lossy(
+                self.buffer
+                    .substr_at(beg, str_)
+                    .expect("bug: Can't be None"),
+            )
+            .into_owned();
+
+            self.suppression_comments.push(SuppressionComment {
+                kind: kind.clone(),
+                name,
+                name_l: self.loc(beg, str_),
+            });
+        }
+    }
 }
@@ -0,0 +1,415 @@
+use std::collections::HashMap;
+
+use crate::lexer::Lexer;
+use crate::source::buffer::{shift_offset, BufferState};
+use crate::str_term::StrTerm;
+use crate::{LexState, StackState, StaticEnvironmentSnapshot, Token, TokenBuf};
+
+/// State of the lexer at a token boundary,
+/// can be used to resume lexing from this point.
+///
+/// Snapshot depends only on the input before `consumed_end()`,
+/// so it stays valid after any edit that starts at or after it.
+#[derive(Debug, Clone)]
+pub struct LexerSnapshot {
+    offset: usize,
+    consumed_end: usize,
+    token_index: usize,
+
+    buffer: BufferState,
+    strterm: Option<Box<StrTerm>>,
+    lex_state: LexState,
+    paren_nest: i32,
+    lpar_beg: i32,
+    brace_nest: i32,
+    cond: StackState,
+    cmdarg: StackState,
+    command_start: bool,
+    token_seen: bool,
+    static_env: StaticEnvironmentSnapshot,
+}
+
+impl LexerSnapshot {
+    /// Byte offset where lexing resumes
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// End of the last line that was read by the lexer
+    pub fn consumed_end(&self) -> usize {
+        self.consumed_end
+    }
+
+    /// Number of tokens emitted before taking the snapshot
+    pub fn token_index(&self) -> usize {
+        self.token_index
+    }
+
+    /// State of the lexer
+    pub fn lex_state(&self) -> LexState {
+        self.lex_state
+    }
+
+    /// Returns `true` if lexing from `self` and from `other`
+    /// (that is taken on the edited input) produces the same tokens.
+    ///
+    /// Both snapshots must be taken on a line that starts after the edit,
+    /// so the rest of the input is the same.
+    fn converges_with(&self, other: &Self, edit: &InputEdit) -> bool {
+        self.buffer.line_begin() >= edit.old_end
+            && other.buffer.line_begin() >= edit.new_end
+            && self
+                .buffer
+                .is_shifted_eq(&other.buffer, edit.old_end, edit.byte_delta())
+            && self.strterm.is_none()
+            && other.strterm.is_none()
+            && self.lex_state == other.lex_state
+            && self.paren_nest == other.paren_nest
+            && self.lpar_beg == other.lpar_beg
+            && self.brace_nest == other.brace_nest
+            && self.cond == other.cond
+            && self.cmdarg == other.cmdarg
+            && self.command_start == other.command_start
+            && self.token_seen == other.token_seen
+            && self.static_env == other.static_env
+    }
+
+    fn shift(&mut self, edit: &InputEdit, line_delta: isize, token_delta: isize) {
+        let (old_end, byte_delta) = (edit.old_end, edit.byte_delta());
+
+        self.offset = shift_offset(self.offset, old_end, byte_delta);
+        self.consumed_end = shift_offset(self.consumed_end, old_end, byte_delta);
+        self.token_index = (self.token_index as isize + token_delta) as usize;
+        self.buffer.shift(old_end, byte_delta, line_delta);
+        if let Some(strterm) = self.strterm.as_mut() {
+            strterm.shift(old_end, byte_delta, line_delta);
+        }
+    }
+}
+
+/// Part of the state of the lexer that is changed by emitting a token
+/// and is read or changed by grammar actions.
+///
+/// Unlike `LexerSnapshot` it doesn't include the position of the buffer
+/// and variables, so it's cheap to capture after every token.
+#[derive(Debug, Clone)]
+pub(crate) struct LexerStepState {
+    strterm: Option<Box<StrTerm>>,
+    lex_state: LexState,
+    paren_nest: i32,
+    lpar_beg: i32,
+    brace_nest: i32,
+    cond: StackState,
+    cmdarg: StackState,
+    command_start: bool,
+    heredoc_indent: i32,
+    heredoc_line_indent: i32,
+}
+
+/// Edit of the input, all offsets are in bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputEdit {
+    /// Start of the edited range
+    pub start: usize,
+
+    /// End of the edited range in the old input
+    pub old_end: usize,
+
+    /// End of the edited range in the new input
+    pub new_end: usize,
+}
+
+impl InputEdit {
+    fn byte_delta(&self) -> isize {
+        self.new_end as isize - self.old_end as isize
+    }
+}
+
+/// Tokens with snapshots of the lexer taken once per line
+#[derive(Debug, Clone, Default)]
+pub struct LineTokens {
+    /// Tokens in the order they were emitted by the lexer
+    pub tokens: Vec<Token>,
+
+    /// Snapshots taken at the first token boundary after reading each line
+    pub snapshots: Vec<LexerSnapshot>,
+}
+
+/// Output of `Lexer::relex`
+#[derive(Debug, Clone)]
+pub struct RelexResult {
+    /// Tokens emitted after resuming and before converging
+    pub tokens: Vec<Token>,
+
+    /// Snapshots taken after resuming and before converging
+    pub snapshots: Vec<LexerSnapshot>,
+
+    /// Index of the old snapshot that lexing was resumed from,
+    /// `None` if lexing started from the beginning of the input
+    pub resumed_from: Option<usize>,
+
+    /// Index of the old snapshot that the state of the lexer converged with,
+    /// `None` if lexing reached the end of the input
+    pub converged_with: Option<usize>,
+
+    edit: InputEdit,
+    line_delta: isize,
+}
+
+impl RelexResult {
+    /// Combines old tokens and snapshots with re-lexed ones,
+    /// returns tokens and snapshots of the new input
+    pub fn splice(self, old: LineTokens) -> LineTokens {
+        let LineTokens {
+            tokens: mut old_tokens,
+            snapshots: mut old_snapshots,
+        } = old;
+
+        let (tokens_head, snapshots_head) = match self.resumed_from {
+            Some(idx) => (old_snapshots[idx].token_index, idx + 1),
+            None => (0, 0),
+        };
+        let (tokens_tail, snapshots_tail) = match self.converged_with {
+            Some(idx) => (old_snapshots[idx].token_index, idx),
+            None => (old_tokens.len(), old_snapshots.len()),
+        };
+
+        let token_delta = (tokens_head + self.tokens.len()) as isize - tokens_tail as isize;
+
+        let mut tokens = old_tokens.drain(..tokens_head).collect::<Vec<_>>();
+        tokens.extend(self.tokens);
+        tokens.extend(
+            old_tokens
+                .drain(tokens_tail - tokens_head..)
+                .map(|mut token| {
+                    let byte_delta = self.edit.byte_delta() as i32;
+                    token.loc = token.loc.adjust_begin(byte_delta).adjust_end(byte_delta);
+                    token
+                }),
+        );
+
+        let mut snapshots = old_snapshots.drain(..snapshots_head).collect::<Vec<_>>();
+        snapshots.extend(self.snapshots);
+        snapshots.extend(old_snapshots.drain(snapshots_tail - snapshots_head..).map(
+            |mut snapshot| {
+                snapshot.shift(&self.edit, self.line_delta, token_delta);
+                snapshot
+            },
+        ));
+
+        LineTokens { tokens, snapshots }
+    }
+}
+
+impl Lexer {
+    /// Captures the current state of the lexer
+    pub fn snapshot(&self) -> LexerSnapshot {
+        LexerSnapshot {
+            offset: self.buffer.pcur,
+            consumed_end: self.buffer.consumed_end(),
+            token_index: 0,
+
+            buffer: self.buffer.state(),
+            strterm: self.strterm.clone(),
+            lex_state: self.lex_state,
+            paren_nest: self.paren_nest,
+            lpar_beg: self.lpar_beg,
+            brace_nest: self.brace_nest,
+            cond: self.cond.clone(),
+            cmdarg: self.cmdarg.clone(),
+            command_start: self.command_start,
+            token_seen: self.token_seen,
+            static_env: self.static_env.snapshot(),
+        }
+    }
+
+    /// Restores the state of the lexer from a given snapshot.
+    ///
+    /// The snapshot can be taken on a different input,
+    /// as long as both inputs are the same before `snapshot.consumed_end()`.
+    pub fn restore(&mut self, snapshot: &LexerSnapshot) {
+        self.buffer.restore_state(&snapshot.buffer);
+        self.strterm = snapshot.strterm.clone();
+        self.lex_state = snapshot.lex_state;
+        self.paren_nest = snapshot.paren_nest;
+        self.lpar_beg = snapshot.lpar_beg;
+        self.brace_nest = snapshot.brace_nest;
+        self.cond = snapshot.cond.clone();
+        self.cmdarg = snapshot.cmdarg.clone();
+        self.command_start = snapshot.command_start;
+        self.token_seen = snapshot.token_seen;
+        self.static_env.restore(&snapshot.static_env);
+
+        self.lval = None;
+        self.lval_start = None;
+        self.lval_end = None;
+        self.tokenbuf = TokenBuf::default();
+    }
+
+    pub(crate) fn step_state(&self) -> LexerStepState {
+        LexerStepState {
+            strterm: self.strterm.clone(),
+            lex_state: self.lex_state,
+            paren_nest: self.paren_nest,
+            lpar_beg: self.lpar_beg,
+            brace_nest: self.brace_nest,
+            cond: self.cond.clone(),
+            cmdarg: self.cmdarg.clone(),
+            command_start: self.command_start,
+            heredoc_indent: self.buffer.heredoc_indent,
+            heredoc_line_indent: self.buffer.heredoc_line_indent,
+        }
+    }
+
+    pub(crate) fn restore_step_state(&mut self, state: &LexerStepState) {
+        self.strterm = state.strterm.clone();
+        self.lex_state = state.lex_state;
+        self.paren_nest = state.paren_nest;
+        self.lpar_beg = state.lpar_beg;
+        self.brace_nest = state.brace_nest;
+        self.cond = state.cond.clone();
+        self.cmdarg = state.cmdarg.clone();
+        self.command_start = state.command_start;
+        self.buffer.heredoc_indent = state.heredoc_indent;
+        self.buffer.heredoc_line_indent = state.heredoc_line_indent;
+    }
+
+    /// Tokenizes given input until EOF, takes a snapshot
+    /// at the first token boundary after reading each line.
+    ///
+    /// Just like `Lexer::tokenize_until_eof` the lexer is not driven by the parser,
+    /// so it's suitable for editors and highlighters that need
+    /// fast incremental updates (see `Lexer::relex`) more than
+    /// a fully correct sequence of tokens.
+    ///
+    /// # Example
+    /// ```
+    /// use lib_ruby_parser::{InputEdit, Lexer};
+    ///
+    /// let old = Lexer::new(b"a = 1\nb = 2\nc = 3\n".to_vec(), "(eval)", None).tokenize_lines();
+    ///
+    /// // "2" -> "42"
+    /// let edit = InputEdit { start: 10, old_end: 11, new_end: 12 };
+    /// let new_input = b"a = 1\nb = 42\nc = 3\n".to_vec();
+    /// let relexed = Lexer::new(new_input.clone(), "(eval)", None).relex(&old.snapshots, edit);
+    /// // the state of the lexer is the same after reading the 3rd line
+    /// assert!(relexed.converged_with.is_some());
+    ///
+    /// let new = relexed.splice(old);
+    /// let expected = Lexer::new(new_input, "(eval)", None).tokenize_lines();
+    /// assert_eq!(new.tokens, expected.tokens);
+    /// ```
+    pub fn tokenize_lines(&mut self) -> LineTokens {
+        let mut result = LineTokens::default();
+        self.lex_lines(&mut result, 0, None, |_| None);
+        result
+    }
+
+    /// Resumes lexing of the edited input from the last snapshot
+    /// that is not affected by the edit, stops once the state of the lexer
+    /// converges with the state before the edit.
+    ///
+    /// `self` must be constructed with the new input,
+    /// `snapshots` must be taken on the old input by `Lexer::tokenize_lines`.
+    /// Use `RelexResult::splice` to get the full list of tokens of the new input.
+    pub fn relex(&mut self, snapshots: &[LexerSnapshot], edit: InputEdit) -> RelexResult {
+        let resumed_from = snapshots
+            .iter()
+            .rposition(|snapshot| self.can_resume_from(snapshot, edit.start));
+        let (token_index, consumed_end) = match resumed_from {
+            Some(idx) => {
+                self.restore(&snapshots[idx]);
+                (
+                    snapshots[idx].token_index,
+                    Some(snapshots[idx].consumed_end),
+                )
+            }
+            None => (0, None),
+        };
+
+        let byte_delta = edit.byte_delta();
+        let old_snapshots_by_offset = snapshots
+            .iter()
+            .enumerate()
+            .filter(|(_, snapshot)| snapshot.offset >= edit.old_end)
+            .map(|(idx, snapshot)| (snapshot.offset, idx))
+            .collect::<HashMap<_, _>>();
+
+        let mut result = LineTokens::default();
+        let mut line_delta = 0;
+        let converged_with = self.lex_lines(&mut result, token_index, consumed_end, |snapshot| {
+            if snapshot.offset < edit.new_end {
+                return None;
+            }
+            let old_offset = snapshot.offset as isize - byte_delta;
+            let idx = *old_snapshots_by_offset.get(&(old_offset as usize))?;
+            let old_snapshot = &snapshots[idx];
+            if !old_snapshot.converges_with(snapshot, &edit) {
+                return None;
+            }
+            line_delta =
+                snapshot.buffer.line_count() as isize - old_snapshot.buffer.line_count() as isize;
+            Some(idx)
+        });
+
+        RelexResult {
+            tokens: result.tokens,
+            snapshots: result.snapshots,
+            resumed_from,
+            converged_with,
+            edit,
+            line_delta,
+        }
+    }
+
+    /// Returns `true` if the state from a given snapshot doesn't depend
+    /// on the input starting from `pos`, i.e. the lexer hasn't reached EOF
+    /// and the last line read before taking the snapshot is not affected by changes at `pos`
+    pub(crate) fn can_resume_from(&self, snapshot: &LexerSnapshot, pos: usize) -> bool {
+        !snapshot.buffer.is_eof() && self.is_consumed_before(snapshot.consumed_end, pos)
+    }
+
+    /// Same as `can_resume_from`, but for the current state of the lexer
+    pub(crate) fn can_resume_from_current(&self, pos: usize) -> bool {
+        !self.buffer.eofp && self.is_consumed_before(self.buffer.consumed_end(), pos)
+    }
+
+    fn is_consumed_before(&self, consumed_end: usize, pos: usize) -> bool {
+        consumed_end < pos
+            || (consumed_end == pos
+                && (pos == 0 || self.buffer.input.byte_at(pos - 1) == Some(b'\n')))
+    }
+
+    /// Lexes until EOF or until `stop` returns an index of the old snapshot,
+    /// takes a snapshot once a new line is read and the buffer doesn't jump
+    /// to another line (after a heredoc or a look-ahead of the next line)
+    fn lex_lines<F>(
+        &mut self,
+        result: &mut LineTokens,
+        token_index: usize,
+        mut consumed_end: Option<usize>,
+        mut stop: F,
+    ) -> Option<usize>
+    where
+        F: FnMut(&LexerSnapshot) -> Option<usize>,
+    {
+        loop {
+            if consumed_end != Some(self.buffer.consumed_end()) && self.buffer.is_sequential() {
+                let mut snapshot = self.snapshot();
+                snapshot.token_index = token_index + result.tokens.len();
+                consumed_end = Some(snapshot.consumed_end);
+
+                if let Some(idx) = stop(&snapshot) {
+                    return Some(idx);
+                }
+                result.snapshots.push(snapshot);
+            }
+
+            let token = self.yylex().take_value();
+            if token.token_type == Self::END_OF_INPUT {
+                return None;
+            }
+            result.tokens.push(token);
+        }
+    }
+}
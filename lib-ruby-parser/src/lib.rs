@@ -27,10 +27,11 @@ pub mod source;
 #[allow(clippy::collapsible_else_if)]
 mod lexer;
 
-pub use lexer::Lexer;
+pub use lexer::{InputEdit, Lexer, LexerSnapshot, LineTokens, RelexResult};
 
 mod static_environment;
 pub use static_environment::StaticEnvironment;
+pub(crate) use static_environment::StaticEnvironmentSnapshot;
 
 pub(crate) mod parse_value;
 
@@ -41,7 +42,7 @@ mod parser_result;
 pub use parser_result::ParserResult;
 
 mod parser;
-pub use parser::Parser;
+pub use parser::{Parser, ParserCheckpoint};
 
 mod builder;
 pub(crate) use builder::Builder;
@@ -56,7 +57,10 @@ mod variables_stack;
 pub(crate) use variables_stack::VariablesStack;
 
 mod error;
-pub use error::{Diagnostic, DiagnosticMessage, ErrorLevel};
+pub use error::{
+    Diagnostic, DiagnosticAction, DiagnosticHandler, DiagnosticHandlerFn, DiagnosticMessage,
+    ErrorLevel,
+};
 
 pub(crate) mod maybe_byte;
 
@@ -76,8 +80,12 @@ pub(crate) use stack_state::StackState;
 pub(crate) mod str_term;
 
 mod context;
+pub use context::ContextFlags;
 pub(crate) use context::SharedContext;
 
+mod trace;
+pub use trace::{TraceEvent, Tracer, TracerFn};
+
 /// Module with all known node types
 pub mod nodes;
 pub use nodes::Node;
@@ -85,8 +93,34 @@ pub use nodes::Node;
 /// Module to perform recursive traversing
 pub mod traverse;
 
+/// Module with semantic token classification for syntax highlighting
+pub mod semantic_tokens;
+
+/// Module with lossless concrete syntax tree
+pub mod cst;
+
+/// Module with incomplete input detection for REPLs
+pub mod completeness;
+
+mod syntax_suggest;
+
+/// Module with indentation and keyword/bracket pair matching for editors
+pub mod indentation;
+
+/// Module with folding and selection ranges for editors
+pub mod ranges;
+
+/// Module with a nested outline of classes, modules, methods and constants
+pub mod symbols;
+
+/// Module with lexical scopes and local variable bindings
+pub mod scopes;
+
 mod token;
 pub use token::Token;
 
+mod token_kind;
+pub use token_kind::{TokenKind, UnknownTokenType};
+
 mod bytes;
 pub use bytes::Bytes;
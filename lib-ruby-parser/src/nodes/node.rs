@@ -25,4 +25,9 @@ impl Node {
     pub fn print_with_locs(&self) {
         self.inner_ref().print_with_locs()
     }
+
+    /// Returns direct child nodes in the order of fields
+    pub(crate) fn children(&self) -> Vec<&Node> {
+        self.inner_ref().children()
+    }
 }
@@ -0,0 +1,101 @@
+use crate::lexer::LexerStepState;
+use crate::source::{Comment, MagicComment, SuppressionComment};
+use crate::{Diagnostic, LexerSnapshot, Parser, ParserOptions, Token};
+
+/// A token consumed by the parser together with the part of the state
+/// of the lexer that is visible to grammar actions, right after emitting the token
+#[derive(Debug, Clone)]
+pub(crate) struct CheckpointStep {
+    pub(crate) token: Token,
+    pub(crate) lexer: LexerStepState,
+}
+
+/// The last step that a checkpoint can be resumed from,
+/// recorded while the parser consumes the prefix
+#[derive(Debug)]
+pub(crate) struct CheckpointEnd {
+    pub(crate) steps_count: usize,
+    pub(crate) lexer: LexerSnapshot,
+
+    pub(crate) diagnostics_count: usize,
+    pub(crate) comments_count: usize,
+    pub(crate) magic_comments_count: usize,
+    pub(crate) suppression_comments_count: usize,
+}
+
+/// State of the parser after consuming a prefix of the input,
+/// see `Parser::checkpoint`.
+///
+/// Bison keeps its stack in local variables of the generated parsing loop,
+/// so instead of copying it the checkpoint records tokens consumed by the parser
+/// along with the few lexer fields that grammar actions depend on.
+/// A full snapshot of the lexer is kept only for the last token.
+/// A forked parser replays these tokens without lexing and without reporting
+/// diagnostics of the prefix again, which rebuilds the stack,
+/// `StaticEnvironment` and context in exactly the same state.
+///
+/// Keep in mind that a fork re-runs the grammar over the whole prefix:
+/// every grammar action runs again and the AST of the prefix is built again,
+/// only lexing of the prefix is skipped. So each fork costs O(prefix),
+/// close to parsing the prefix followed by the continuation from scratch.
+#[derive(Debug, Clone)]
+pub struct ParserCheckpoint {
+    pub(crate) prefix: Vec<u8>,
+    pub(crate) steps: Vec<CheckpointStep>,
+    pub(crate) lexer: Option<LexerSnapshot>,
+
+    pub(crate) diagnostics: Vec<Diagnostic>,
+    pub(crate) comments: Vec<Comment>,
+    pub(crate) magic_comments: Vec<MagicComment>,
+    pub(crate) suppression_comments: Vec<SuppressionComment>,
+}
+
+impl ParserCheckpoint {
+    /// Input that was given to the parser that took the checkpoint
+    pub fn prefix(&self) -> &[u8] {
+        &self.prefix
+    }
+
+    /// Number of bytes of the prefix that are covered by the checkpoint.
+    ///
+    /// The state of the lexer can't be saved in the middle of a line
+    /// (or a multi-line construction like a heredoc), so a forked parser
+    /// lexes everything after this offset again.
+    pub fn consumed_len(&self) -> usize {
+        self.lexer.as_ref().map_or(0, |lexer| lexer.consumed_end())
+    }
+
+    /// Constructs a parser of the prefix followed by `continuation`,
+    /// the parser starts from the state saved in the checkpoint.
+    ///
+    /// The checkpoint is not consumed, so it can be forked any number of times.
+    /// Every fork replays all tokens of the prefix through the grammar,
+    /// see `ParserCheckpoint` for what is (and what is not) saved.
+    ///
+    /// # Example
+    /// ```
+    /// use lib_ruby_parser::{Parser, ParserOptions};
+    ///
+    /// let checkpoint = Parser::new("def foo\n  bar(1,\n", ParserOptions::default()).checkpoint();
+    ///
+    /// let ok = |continuation: &str| {
+    ///     let result = checkpoint
+    ///         .fork(continuation, ParserOptions::default())
+    ///         .do_parse();
+    ///     result.diagnostics.is_empty()
+    /// };
+    ///
+    /// assert!(ok("2)\nend\n"));
+    /// assert!(!ok("2)\n"));
+    /// assert!(!ok("end\n"));
+    /// ```
+    pub fn fork<TInput>(&self, continuation: TInput, options: ParserOptions) -> Parser
+    where
+        TInput: Into<Vec<u8>>,
+    {
+        let mut input = self.prefix.clone();
+        input.extend(continuation.into());
+
+        Parser::new(input, options).resume_from(self)
+    }
+}
@@ -1,2 +1,5 @@
 mod parse;
 pub use parse::{token_name, Parser};
+
+mod checkpoint;
+pub use checkpoint::ParserCheckpoint;
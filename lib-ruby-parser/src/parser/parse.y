@@ -2,7 +2,7 @@
 
 %define api.parser.struct { Parser }
 %define api.value.type { Value }
-%define api.parser.check_debug { self.is_debug() }
+%define api.parser.check_debug { false }
 
 %define parse.error custom
 %define parse.trace
@@ -45,15 +45,27 @@
     tokens: Vec<Token>,
     diagnostics: Diagnostics,
     token_rewriter: Option<TokenRewriter>,
+    injected_tokens: VecDeque<Token>,
+    replayed_steps: VecDeque<CheckpointStep>,
+    replayed_lexer: Option<LexerSnapshot>,
+    checkpoint_steps: Option<Vec<CheckpointStep>>,
+    checkpoint_end: Option<CheckpointEnd>,
     record_tokens: bool,
+    record_trivia: bool,
+    verbose: bool,
+    warn_indent: bool,
+    tokens_only: bool,
+    suggest_unclosed_block: bool,
     tokens_pool: Pool<Token>,
+    tracer: Option<Rc<Tracer>>,
+    traced_lookahead: Option<Token>,
 }
 
 %code use {
 
 use alloc_from_pool::{Pool, PoolValue};
 use crate::{ParserOptions, ParserResult};
-use crate::{Token};
+use crate::{Token, Bytes};
 use crate::{Lexer, Builder, CurrentArgStack, StaticEnvironment, MaxNumparamStack, VariablesStack};
 use crate::lex_states::*;
 use crate::{SharedContext as ParserContext, context::Context};
@@ -65,8 +77,19 @@ use crate::Node;
 use crate::nodes;
 use crate::{Diagnostic, DiagnosticMessage, ErrorLevel};
 use crate::error::Diagnostics;
-use crate::source::token_rewriter::{TokenRewriter, TokenRewriterResult, LexStateAction, RewriteAction};
+use crate::error::suppressions::apply_suppressions;
+use crate::source::insert_trivia_tokens;
+use crate::source::token_rewriter::{TokenRewriter, TokenRewriterContext, TokenRewriterResult, LexStateAction, RewriteAction};
 use crate::Loc;
+use crate::{TraceEvent, Tracer};
+use crate::source::MagicCommentKind;
+use crate::source::SharedLines;
+use crate::syntax_suggest::unclosed_block;
+use crate::source::line_of;
+use crate::parser::checkpoint::{CheckpointEnd, CheckpointStep, ParserCheckpoint};
+use crate::LexerSnapshot;
+use std::collections::VecDeque;
+use std::rc::Rc;
 
 }
 
@@ -358,14 +381,18 @@ use crate::Loc;
 
                         self.current_arg_stack.pop();
                         self.max_numparam_stack.pop();
+                        self.warn_unused_variables();
                     }
                 ;
 
     top_compstmt: top_stmts opt_terms
                     {
-                        // TODO: run void_stmts
+                        let stmts = $<NodeList>1;
+                        if self.verbose {
+                            self.builder.void_stmts(&stmts);
+                        }
                         $$ = Value::MaybeNode(
-                            self.builder.compstmt($<NodeList>1)
+                            self.builder.compstmt(stmts)
                         );
                     }
                 ;
@@ -376,17 +403,15 @@ use crate::Loc;
                     }
                 | top_stmt
                     {
-                      $$ = Value::NodeList( Box::new(vec![ $<Node>1 ]) );
+                      $$ = Value::NodeList( self.push_top_stmt(Box::new(vec![]), $<Node>1) );
                     }
                 | top_stmts terms top_stmt
                     {
-                        let mut nodes = $<BoxedNodeList>1;
-                        nodes.push( $<Node>3 );
-                        $$ = Value::NodeList( nodes );
+                        $$ = Value::NodeList( self.push_top_stmt($<BoxedNodeList>1, $<Node>3) );
                     }
                 | error top_stmt
                     {
-                      $$ = Value::NodeList( Box::new(vec![ $<Node>2 ]) );
+                      $$ = Value::NodeList( self.push_top_stmt(Box::new(vec![]), $<Node>2) );
                     }
                 ;
 
@@ -459,9 +484,12 @@ use crate::Loc;
 
         compstmt: stmts opt_terms
                     {
-                        // TODO: run void_stmts
+                        let stmts = $<NodeList>1;
+                        if self.verbose {
+                            self.builder.void_stmts(&stmts);
+                        }
                         $$ = Value::MaybeNode(
-                            self.builder.compstmt($<NodeList>1)
+                            self.builder.compstmt(stmts)
                         );
                     }
                 ;
@@ -2926,6 +2954,7 @@ use crate::Loc;
                   k_end
                     {
                         self.yylexer.cmdarg.pop();
+                        self.warn_mismatched_indentation(@1, "begin", @4);
 
                         $$ = Value::Node(
                             self.builder.begin_keyword($<Token>1, $<MaybeBoxedNode>3, $<Token>4)
@@ -3133,6 +3162,7 @@ use crate::Loc;
                   if_tail
                   k_end
                     {
+                        self.warn_mismatched_indentation(@1, "if", @6);
                         let IfTail { keyword_t, body: else_body } = $<IfTail>5;
 
                         $$ = Value::Node(
@@ -3152,6 +3182,7 @@ use crate::Loc;
                   opt_else
                   k_end
                     {
+                        self.warn_mismatched_indentation(@1, "unless", @6);
                         let (else_t, body) = $<OptElse>5.map(|else_| (Some(else_.else_t), else_.body)).unwrap_or_else(|| (None, None));
 
                         $$ = Value::Node(
@@ -3170,6 +3201,7 @@ use crate::Loc;
                   compstmt
                   k_end
                     {
+                        self.warn_mismatched_indentation(@1, "while", @4);
                         let ExprValueDo { value, do_t } = $<ExprValueDo>2;
                         $$ = Value::Node(
                             self.builder.loop_(
@@ -3186,6 +3218,7 @@ use crate::Loc;
                   compstmt
                   k_end
                     {
+                        self.warn_mismatched_indentation(@1, "until", @4);
                         let ExprValueDo { value, do_t } = $<ExprValueDo>2;
                         $$ = Value::Node(
                             self.builder.loop_(
@@ -3207,6 +3240,7 @@ use crate::Loc;
                   case_body
                   k_end
                     {
+                        self.warn_mismatched_indentation(@1, "case", @6);
                         let CaseBody { when_bodies, opt_else } = $<CaseBody>5;
                         let (else_t, else_body) = opt_else.map(|else_| (Some(else_.else_t), else_.body)).unwrap_or_else(|| (None, None));
 
@@ -3230,6 +3264,7 @@ use crate::Loc;
                   case_body
                   k_end
                     {
+                        self.warn_mismatched_indentation(@1, "case", @5);
                         let CaseBody { when_bodies, opt_else } = $<CaseBody>4;
                         let (else_t, else_body) = opt_else.map(|else_| (Some(else_.else_t), else_.body)).unwrap_or_else(|| (None, None));
 
@@ -3248,6 +3283,7 @@ use crate::Loc;
                   p_case_body
                   k_end
                     {
+                        self.warn_mismatched_indentation(@1, "case", @5);
                         let PCaseBody { in_bodies, opt_else } = $<PCaseBody>4;
                         let (else_t, else_body) = opt_else.map(|else_| (Some(else_.else_t), else_.body)).unwrap_or_else(|| (None, None));
 
@@ -3266,6 +3302,7 @@ use crate::Loc;
                   compstmt
                   k_end
                     {
+                        self.warn_mismatched_indentation(@1, "for", @6);
                         let ExprValueDo { value, do_t } = $<ExprValueDo>4;
                         $$ = Value::Node(
                             self.builder.for_(
@@ -3288,6 +3325,7 @@ use crate::Loc;
                   bodystmt
                   k_end
                     {
+                        self.warn_mismatched_indentation(@1, "class", @6);
                         let TokenWithContext { token: k_class, ctx } = $<TokenWithContext>1;
                         if self.context.in_def() {
                             return self.yyerror(&k_class.loc, DiagnosticMessage::ClassDefinitionInMethodBody {});
@@ -3320,6 +3358,7 @@ use crate::Loc;
                   bodystmt
                   k_end
                     {
+                        self.warn_mismatched_indentation(@1, "class", @7);
                         let TokenWithContext { token: k_class, ctx } = $<TokenWithContext>1;
                         $$ = Value::Node(
                             self.builder.def_sclass(
@@ -3344,6 +3383,7 @@ use crate::Loc;
                   bodystmt
                   k_end
                     {
+                        self.warn_mismatched_indentation(@1, "module", @5);
                         let TokenWithContext { token: k_module, ctx } = $<TokenWithContext>1;
                         if self.context.in_def() {
                             return self.yyerror(&k_module.loc, DiagnosticMessage::ModuleDefinitionInMethodBody {});
@@ -3363,6 +3403,7 @@ use crate::Loc;
                     }
                 | defn_head f_arglist bodystmt k_end
                     {
+                        self.warn_mismatched_indentation(@1, "def", @4);
                         let DefnHead { def_t, name_t } = $<DefnHead>1;
                         let TokenWithContext { token: name_t, ctx } = name_t;
 
@@ -3382,6 +3423,7 @@ use crate::Loc;
                     }
                 | defs_head f_arglist bodystmt k_end
                     {
+                        self.warn_mismatched_indentation(@1, "def", @4);
                         let DefsHead { def_t, definee, dot_t, name_t } = $<DefsHead>1;
                         let TokenWithContext { token: name_t, ctx } = name_t;
 
@@ -4109,6 +4151,7 @@ opt_block_args_tail:
                         let LambdaBody { begin_t, body, end_t } = $<LambdaBody>6;
 
                         self.max_numparam_stack.pop();
+                        self.warn_unused_variables();
                         self.static_env.unextend();
                         self.yylexer.cmdarg.pop();
                         self.context.set_in_lambda($<Context>3.in_lambda());
@@ -4177,6 +4220,7 @@ opt_block_args_tail:
                     }
                   bodystmt k_end
                     {
+                        self.warn_mismatched_indentation(@1, "do", @4);
                         self.context.set_in_lambda($<Context>2.in_lambda());
                         $$ = Value::new_lambda_body(
                             LambdaBody {
@@ -4195,6 +4239,7 @@ opt_block_args_tail:
                     }
                   do_body k_end
                     {
+                        self.warn_mismatched_indentation(@1, "do", @4);
                         self.context.set_in_block($<Context>2.in_block());
                         let DoBody { args_type, body } = $<DoBody>3;
                         $$ = Value::new_do_block(
@@ -4435,6 +4480,7 @@ opt_block_args_tail:
                     }
                   do_body k_end
                     {
+                        self.warn_mismatched_indentation(@1, "do", @4);
                         let DoBody { args_type, body } = $<DoBody>3;
                         self.context.set_in_block($<Context>2.in_block());
 
@@ -4463,6 +4509,7 @@ opt_block_args_tail:
                         };
 
                         self.max_numparam_stack.pop();
+                        self.warn_unused_variables();
                         self.static_env.unextend();
 
                         $$ = Value::new_brace_body(
@@ -4489,6 +4536,7 @@ opt_block_args_tail:
                         };
 
                         self.max_numparam_stack.pop();
+                        self.warn_unused_variables();
                         self.static_env.unextend();
                         self.yylexer.cmdarg.pop();
 
@@ -6344,7 +6392,7 @@ f_opt_paren_args: f_paren_args
                     {
                         let arg_t = $<Token>1;
                         let arg_name = clone_value(&arg_t);
-                        self.current_arg_stack.set(Some(arg_name));
+                        self.current_arg_stack.set(Some((arg_name, arg_t.loc)));
                         $$ = Value::Token(arg_t);
                     }
                 ;
@@ -6391,7 +6439,7 @@ f_opt_paren_args: f_paren_args
 
                         self.max_numparam_stack.set_has_ordinary_params();
 
-                        self.current_arg_stack.set(Some(ident));
+                        self.current_arg_stack.set(Some((ident, ident_t.loc)));
                         self.context.set_in_argdef(false);
 
                         $$ = Value::Token(ident_t);
@@ -6902,25 +6950,35 @@ impl Parser {
             decoder,
             token_rewriter,
             record_tokens,
+            record_trivia,
+            verbose,
+            warn_indent,
+            suggest_unclosed_block,
+            on_diagnostic,
+            tracer,
         } = options;
 
-        let context = ParserContext::new();
+        let tracer = tracer.map(Rc::new);
+        let context = ParserContext::new(tracer.clone(), verbose);
         let current_arg_stack = CurrentArgStack::new();
         let max_numparam_stack = MaxNumparamStack::new();
         let pattern_variables = VariablesStack::new();
         let pattern_hash_keys = VariablesStack::new();
         let static_env = StaticEnvironment::new();
-        let diagnostics = Diagnostics::new();
+        let diagnostics = Diagnostics::new(on_diagnostic);
         let tokens_pool = Pool::new();
 
         let input: Vec<u8> = input.into();
         let buffer_name: String = buffer_name;
 
         let mut lexer = Lexer::new(input, buffer_name, decoder);
+        let lines = SharedLines::new(&lexer.buffer.input.decoded.lines);
         lexer.context = context.clone();
         lexer.static_env = static_env.clone();
         lexer.diagnostics = diagnostics.clone();
+        lexer.lines = lines.clone();
         lexer.tokens_factory = tokens_pool.factory();
+        lexer.tracer = tracer.clone();
 
         let builder = Builder::new(
             static_env.clone(),
@@ -6930,6 +6988,7 @@ impl Parser {
             pattern_variables.clone(),
             pattern_hash_keys.clone(),
             diagnostics.clone(),
+            lines,
             tokens_pool.factory(),
         );
 
@@ -6953,8 +7012,20 @@ impl Parser {
             diagnostics,
             yylexer: lexer,
             token_rewriter,
+            injected_tokens: VecDeque::new(),
+            replayed_steps: VecDeque::new(),
+            replayed_lexer: None,
+            checkpoint_steps: None,
+            checkpoint_end: None,
             record_tokens,
+            record_trivia,
+            verbose,
+            warn_indent,
+            tokens_only: false,
+            suggest_unclosed_block,
             tokens_pool,
+            tracer,
+            traced_lookahead: None,
         }
     }
 
@@ -6968,29 +7039,122 @@ impl Parser {
     pub fn do_parse(mut self) -> ParserResult  {
         self.parse();
 
-        ParserResult {
-            ast: self.result,
-            tokens: self.tokens,
-            diagnostics: self.diagnostics.take_inner(),
-            comments: self.yylexer.comments,
-            magic_comments: self.yylexer.magic_comments,
-            input: self.yylexer.buffer.input.decoded,
+        self.into_result()
+    }
+
+    /// Parses given input and returns only recorded tokens,
+    /// see `Lexer::tokenize`
+    pub(crate) fn do_tokenize(mut self) -> Vec<Token> {
+        self.tokens_only = true;
+        self.record_tokens = true;
+        self.parse();
+
+        if self.record_trivia {
+            insert_trivia_tokens(self.tokens, &self.yylexer.buffer.input.decoded, &self.yylexer.comments)
+        } else {
+            self.tokens
         }
     }
 
+    /// Parses given input as a prefix of some longer input
+    /// and returns a checkpoint that can be used to parse
+    /// different continuations of it, see `ParserCheckpoint::fork`.
+    ///
+    /// The checkpoint covers the longest part of the input that the lexer
+    /// has fully consumed before reaching the end of the input.
+    pub fn checkpoint(mut self) -> ParserCheckpoint {
+        self.checkpoint_steps = Some(vec![]);
+        self.parse();
+
+        let mut steps = self.checkpoint_steps.take().unwrap_or_default();
+
+        let (lexer, diagnostics_count, comments_count, magic_comments_count, suppression_comments_count) =
+            match self.checkpoint_end.take() {
+                Some(end) => {
+                    steps.truncate(end.steps_count);
+                    (
+                        Some(end.lexer),
+                        end.diagnostics_count,
+                        end.comments_count,
+                        end.magic_comments_count,
+                        end.suppression_comments_count,
+                    )
+                }
+                None => {
+                    steps.clear();
+                    (None, 0, 0, 0, 0)
+                }
+            };
+
+        let mut diagnostics = self.diagnostics.to_vec();
+        diagnostics.truncate(diagnostics_count);
+        let mut comments = std::mem::take(&mut self.yylexer.comments);
+        comments.truncate(comments_count);
+        let mut magic_comments = std::mem::take(&mut self.yylexer.magic_comments);
+        magic_comments.truncate(magic_comments_count);
+        let mut suppression_comments = std::mem::take(&mut self.yylexer.suppression_comments);
+        suppression_comments.truncate(suppression_comments_count);
+
+        ParserCheckpoint {
+            prefix: self.yylexer.buffer.input.as_shared_bytes().to_vec(),
+            steps,
+            lexer,
+            diagnostics,
+            comments,
+            magic_comments,
+            suppression_comments,
+        }
+    }
+
+    /// Makes the parser replay tokens of a given checkpoint
+    /// before lexing the rest of the input
+    pub(crate) fn resume_from(mut self, checkpoint: &ParserCheckpoint) -> Self {
+        if checkpoint.steps.is_empty() {
+            return self;
+        }
+
+        self.replayed_steps = checkpoint.steps.iter().cloned().collect();
+        self.replayed_lexer = checkpoint.lexer.clone();
+        self.diagnostics.restore(checkpoint.diagnostics.clone());
+        self.diagnostics.set_muted(true);
+        self.yylexer.comments = checkpoint.comments.clone();
+        self.yylexer.magic_comments = checkpoint.magic_comments.clone();
+        self.yylexer.suppression_comments = checkpoint.suppression_comments.clone();
+
+        self
+    }
+
     #[doc(hidden)]
     pub fn do_parse_with_state_validation(mut self) -> ParserResult {
         self.parse();
 
         self.assert_state_is_final();
 
+        self.into_result()
+    }
+
+    fn into_result(self) -> ParserResult {
+        let aborted = self.diagnostics.is_aborted();
+        let input = self.yylexer.buffer.input.decoded;
+        let diagnostics = apply_suppressions(
+            self.diagnostics.take_inner(),
+            &self.yylexer.suppression_comments,
+            &input,
+        );
+        let tokens = if self.record_trivia {
+            insert_trivia_tokens(self.tokens, &input, &self.yylexer.comments)
+        } else {
+            self.tokens
+        };
+
         ParserResult {
-            ast: self.result,
-            tokens: self.tokens,
-            diagnostics: self.diagnostics.take_inner(),
+            ast: if aborted { None } else { self.result },
+            tokens,
+            diagnostics,
             comments: self.yylexer.comments,
             magic_comments: self.yylexer.magic_comments,
-            input: self.yylexer.buffer.input.decoded,
+            input,
+            aborted,
         }
     }
 
@@ -6999,43 +7163,130 @@ impl Parser {
             level: ErrorLevel::Warning,
             message,
             loc: *loc,
+            related: vec![],
         };
         self.diagnostics.emit(diagnostic);
     }
 
+    // MRI's `-w` "assigned but unused variable" warnings,
+    // emitted for the current scope right before it's popped
+    fn warn_unused_variables(&mut self) {
+        if !self.verbose {
+            return;
+        }
+        for (name, loc) in self.static_env.unused_variables() {
+            // `_` and `_foo` are intentionally unused
+            if name.starts_with('_') {
+                continue;
+            }
+            self.warn(&loc, DiagnosticMessage::AssignedButUnusedVariable { name });
+        }
+    }
+
     fn yylex(&mut self) -> PoolValue<Token> {
         self.yylexer.yylex()
     }
 
+    #[allow(clippy::box_collection)]
+    fn push_top_stmt(&self, mut nodes: Box<Vec<Node>>, node: Node) -> Box<Vec<Node>> {
+        // in tokens-only mode top-level statements are dropped
+        // right after they are parsed, so the AST of the whole input is never kept
+        // (nodes of a single statement are still built by grammar actions)
+        if !self.tokens_only {
+            nodes.push(node);
+        }
+        nodes
+    }
+
     fn next_token(&mut self) -> PoolValue<Token> {
-        let mut token = self.yylex();
+        if self.diagnostics.is_aborted() {
+            return self.end_of_input_token();
+        }
 
-        if let Some(token_rewriter) = self.token_rewriter.as_ref() {
-            let boxed_token = token.take_boxed_value();
-            let TokenRewriterResult { rewritten_token, token_action, lex_state_action } =
-                token_rewriter.call(boxed_token, self.yylexer.buffer.input.as_shared_bytes());
+        if let Some(step) = self.replayed_steps.pop_front() {
+            return self.replay_step(step);
+        }
 
-            match lex_state_action {
-                LexStateAction::Keep => {
-                    // keep
-                }
-                LexStateAction::Set(next_state) => {
-                    self.yylexer.lex_state.set(next_state);
+        let token = match self.injected_tokens.pop_front() {
+            // injected tokens are not passed to the rewriter again
+            Some(token) => self.tokens_pool.alloc(token),
+            None => {
+                let token = self.yylex();
+                match self.rewrite_token(token) {
+                    Some(token) => token,
+                    None => return self.next_token(),
                 }
             }
+        };
 
-            match token_action {
-                RewriteAction::Drop => {
-                    return self.next_token();
-                }
-                RewriteAction::Keep => {
-                    token = self.tokens_pool.alloc(*rewritten_token);
-                }
+        self.record_checkpoint_step(&token);
+
+        self.shift_token(token)
+    }
+
+    fn record_checkpoint_step(&mut self, token: &Token) {
+        let steps = match self.checkpoint_steps.as_mut() {
+            Some(steps) => steps,
+            None => return,
+        };
+        steps.push(CheckpointStep {
+            token: token.clone(),
+            lexer: self.yylexer.step_state(),
+        });
+        let steps_count = steps.len();
+
+        // a full snapshot of the lexer is taken only once per line,
+        // resuming from any token of the line covers the same part of the input
+        let consumed_end = self.yylexer.buffer.consumed_end();
+        if self.checkpoint_end.as_ref().map_or(false, |end| end.lexer.consumed_end() == consumed_end) {
+            return;
+        }
+
+        let resumable = token.token_type != Lexer::END_OF_INPUT
+            && self.injected_tokens.is_empty()
+            // changing encoding re-decodes the whole input,
+            // so everything after an encoding comment has to be lexed again
+            && !self.yylexer.magic_comments.iter().any(|comment| comment.kind == MagicCommentKind::Encoding)
+            && self.yylexer.can_resume_from_current(self.yylexer.buffer.input.len());
+
+        if resumable {
+            self.checkpoint_end = Some(CheckpointEnd {
+                steps_count,
+                lexer: self.yylexer.snapshot(),
+                diagnostics_count: self.diagnostics.count(),
+                comments_count: self.yylexer.comments.len(),
+                magic_comments_count: self.yylexer.magic_comments.len(),
+                suppression_comments_count: self.yylexer.suppression_comments.len(),
+            });
+        }
+    }
+
+    /// Passes a token from a checkpoint to the parser as if it was just emitted by the lexer
+    fn replay_step(&mut self, step: CheckpointStep) -> PoolValue<Token> {
+        let CheckpointStep { token, lexer } = step;
+
+        if self.replayed_steps.is_empty() {
+            // everything after the last replayed token is new
+            if let Some(snapshot) = self.replayed_lexer.take() {
+                self.yylexer.restore(&snapshot);
             }
+            self.diagnostics.set_muted(false);
+        } else {
+            self.yylexer.restore_step_state(&lexer);
         }
 
+        let token = self.tokens_pool.alloc(token);
+        self.shift_token(token)
+    }
+
+    fn shift_token(&mut self, token: PoolValue<Token>) -> PoolValue<Token> {
         self.last_token_type = token.token_type;
 
+        if self.tracer.is_some() {
+            // reported by `trace_shift` once the parser actually shifts it
+            self.traced_lookahead = Some(token.clone().take_value());
+        }
+
         if self.record_tokens {
             let token = token.clone();
             self.tokens.push(token.take_value());
@@ -7044,6 +7295,56 @@ impl Parser {
         token
     }
 
+    fn rewrite_token(&mut self, mut token: PoolValue<Token>) -> Option<PoolValue<Token>> {
+        let token_rewriter = match self.token_rewriter.as_ref() {
+            Some(token_rewriter) => token_rewriter,
+            None => return Some(token),
+        };
+
+        let boxed_token = token.take_boxed_value();
+        let rewriter_context = TokenRewriterContext {
+            input: self.yylexer.buffer.input.as_shared_bytes(),
+            lex_state: self.yylexer.lex_state,
+            context: self.context.flags(),
+        };
+        let TokenRewriterResult { rewritten_token, token_action, lex_state_action } =
+            token_rewriter.call(boxed_token, &rewriter_context);
+
+        match lex_state_action {
+            LexStateAction::Keep => {
+                // keep
+            }
+            LexStateAction::Set(next_state) => {
+                let before = self.yylexer.lex_state;
+                self.yylexer.lex_state = next_state;
+                if let Some(tracer) = self.tracer.as_ref() {
+                    tracer.call(&TraceEvent::LexStateChanged { before, after: self.yylexer.lex_state });
+                }
+            }
+        }
+
+        match token_action {
+            RewriteAction::Drop => None,
+            RewriteAction::Keep => Some(self.tokens_pool.alloc(*rewritten_token)),
+            RewriteAction::Replace(tokens) => {
+                self.injected_tokens.extend(tokens);
+                let token = self.injected_tokens.pop_front()?;
+                Some(self.tokens_pool.alloc(token))
+            }
+        }
+    }
+
+    fn end_of_input_token(&mut self) -> PoolValue<Token> {
+        let pos = self.yylexer.buffer.pcur;
+        self.tokens_pool.alloc(Token {
+            token_type: Lexer::END_OF_INPUT,
+            token_value: Bytes::new(vec![]),
+            loc: Loc { begin: pos, end: pos },
+            lex_state_before: self.yylexer.lex_state,
+            lex_state_after: self.yylexer.lex_state,
+        })
+    }
+
     fn check_kwarg_name(&self, ident_t: &Token) -> Result<(), ()> {
         let name = clone_value(ident_t);
         let first_char = name.as_str().chars().next().expect("kwarg name can't be empty");
@@ -7055,7 +7356,8 @@ impl Parser {
                 Diagnostic {
                     level: ErrorLevel::Error,
                     message: DiagnosticMessage::ConstArgument {},
-                    loc
+                    loc,
+                    related: vec![],
                 }
             );
             Err(())
@@ -7081,23 +7383,96 @@ impl Parser {
     }
 
     fn yyerror1(&mut self, message: DiagnosticMessage, loc: Loc) -> Result<i32, ()> {
-        let diagnostic = Diagnostic { level: ErrorLevel::Error, message, loc };
+        let diagnostic = Diagnostic { level: ErrorLevel::Error, message, loc, related: vec![] };
         self.diagnostics.emit(diagnostic);
         Err(())
     }
 
     fn report_syntax_error(&mut self, _stack: &YYStack, yytoken: &SymbolKind, loc: YYLoc) {
         let id: usize = yytoken.code().try_into().expect("failed to convert token code into i32, is it too big?");
+        let mut related = vec![];
+        if self.last_token_type == Lexer::END_OF_INPUT
+            && self.suggest_unclosed_block
+            && !self.diagnostics.is_aborted()
+        {
+            if let Some(block_loc) = unclosed_block(self.yylexer.buffer.input.as_shared_bytes()) {
+                related.push((block_loc, String::from("this block is probably not closed")));
+            }
+        }
         let diagnostic = Diagnostic {
             level: ErrorLevel::Error,
             message: DiagnosticMessage::UnexpectedToken {
                 token_name: Lexer::TOKEN_NAMES[id].to_string()
             },
             loc,
+            related,
         };
         self.diagnostics.emit(diagnostic);
     }
 
+    // MRI's `token_info_warn`, an `end` must be in the same column as its opening keyword.
+    // Keywords in the middle of a line (like `x = if` or `foo do`) are not checked
+    fn warn_mismatched_indentation(&mut self, opening_l: &Loc, opening: &str, end_l: &Loc) {
+        if self.tokens_only || !self.is_warn_indent_enabled(end_l.begin) {
+            return;
+        }
+
+        let (opening_line, opening_indent, opening_starts_line) = self.line_indentation(opening_l.begin);
+        let (end_line, end_indent, end_starts_line) = self.line_indentation(end_l.begin);
+
+        if opening_line == end_line
+            || !opening_starts_line
+            || !end_starts_line
+            || opening_indent == end_indent
+        {
+            return;
+        }
+
+        self.warn(
+            end_l,
+            DiagnosticMessage::MismatchedIndentation {
+                opening: opening.to_string(),
+                line: opening_line + 1,
+            },
+        );
+    }
+
+    fn is_warn_indent_enabled(&self, pos: usize) -> bool {
+        if self.warn_indent {
+            return true;
+        }
+
+        // the last `# warn_indent: true/false` comment before `pos` wins
+        let input = &self.yylexer.buffer.input.decoded;
+        self.yylexer
+            .magic_comments
+            .iter()
+            .rev()
+            .find(|comment| comment.kind == MagicCommentKind::WarnIndent && comment.value_l.end <= pos)
+            .and_then(|comment| input.bytes.get(comment.value_l.begin..comment.value_l.end))
+            .map(|value| value.eq_ignore_ascii_case(b"true"))
+            .unwrap_or(false)
+    }
+
+    // Returns a line of a given byte offset, a width of its indentation
+    // (tabs are expanded to multiples of 8, like in MRI)
+    // and `true` if there's only whitespace before the offset on its line
+    fn line_indentation(&self, pos: usize) -> (usize, usize, bool) {
+        let input = &self.yylexer.buffer.input.decoded;
+        let line = line_of(&input.lines, pos);
+        let mut idx = input.lines.get(line).map(|line| line.start).unwrap_or(0);
+        let mut width = 0;
+        loop {
+            match input.bytes.get(idx) {
+                Some(b' ') => width += 1,
+                Some(b'\t') => width = (width / 8 + 1) * 8,
+                _ => break,
+            }
+            idx += 1;
+        }
+        (line, width, idx == pos)
+    }
+
     fn warn_eol(&mut self, loc: &Loc, tok: &str) {
         if self.yylexer.buffer.is_looking_at_eol() {
             self.warn(loc, DiagnosticMessage::TokAtEolWithoutExpression { token_name: tok.to_string() });
@@ -7122,9 +7497,43 @@ impl Parser {
         assert!(self.pattern_hash_keys.is_empty());
     }
 
-    #[inline]
-    fn is_debug(&self) -> bool {
-        cfg!(feature = "debug-parser")
+    // Called by the generated parser right before shifting a lookahead token,
+    // see codegen/y/parser_y.rs
+    fn trace_shift(&mut self) {
+        if let (Some(tracer), Some(token)) = (self.tracer.as_ref(), self.traced_lookahead.take()) {
+            tracer.call(&TraceEvent::Shift { token: &token });
+        }
+    }
+
+    // Called by the generated parser right before running an action of the rule `yyn`,
+    // the rule is rendered from bison tables, see codegen/y/parser_y.rs
+    fn trace_reduce(&self, yyn: i32, yystack: &YYStack) {
+        let tracer = match self.tracer.as_ref() {
+            Some(tracer) => tracer,
+            None => return,
+        };
+
+        let yyn = i32_to_usize(yyn);
+        let rhs_len = i32_to_usize(Self::yyr2_[yyn]);
+        let mut rule = format!("{}:", Self::trace_symbol_name(Self::yyr1_[yyn]));
+        if rhs_len == 0 {
+            rule.push_str(" %empty");
+        }
+        for idx in (0..rhs_len).rev() {
+            let state = i32_to_usize(yystack.state_at(idx));
+            rule.push(' ');
+            rule.push_str(Self::trace_symbol_name(Self::yystos_[state]));
+        }
+
+        tracer.call(&TraceEvent::Reduce { rule: &rule });
+    }
+
+    fn trace_symbol_name(code: i32) -> &'static str {
+        if code < Self::YYNTOKENS_ {
+            Lexer::TOKEN_NAMES[i32_to_usize(code)]
+        } else {
+            SymbolKind::yynames_[i32_to_usize(code)]
+        }
     }
 
     fn local_push(&mut self) {
@@ -7135,6 +7544,7 @@ impl Parser {
     }
 
     fn local_pop(&mut self) {
+        self.warn_unused_variables();
         self.static_env.unextend();
         self.yylexer.cmdarg.pop();
         self.yylexer.cond.pop();
@@ -1,5 +1,7 @@
 use crate::source::token_rewriter::TokenRewriter;
 use crate::source::Decoder;
+use crate::DiagnosticHandler;
+use crate::Tracer;
 
 /// Configuration of the parser
 #[derive(Debug)]
@@ -85,6 +87,213 @@ pub struct ParserOptions {
     /// When set to false `ParserResult.tokens` is guaranteed to be empty.
     /// If you don't need tokens better set it to false to speed up parsing.
     pub record_tokens: bool,
+
+    /// When set to true (together with `record_tokens`) recorded tokens
+    /// also include synthetic trivia tokens: whitespaces, ignored newlines,
+    /// comments, line continuations, `=begin`/`=end` blocks and `__END__`
+    /// (see `Lexer::tWHITESPACE` and other trivia token types).
+    ///
+    /// These tokens are never passed to the parser.
+    /// Tokens are sorted by location, so concatenation
+    /// of all token ranges covers the whole input.
+    ///
+    /// # Example
+    /// ```
+    /// use lib_ruby_parser::{Lexer, Parser, ParserOptions};
+    ///
+    /// let options = ParserOptions {
+    ///     record_trivia: true,
+    ///     ..Default::default()
+    /// };
+    /// let result = Parser::new(b"foo # bar".to_vec(), options).do_parse();
+    /// let token_types = result
+    ///     .tokens
+    ///     .iter()
+    ///     .map(|token| token.token_type)
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert_eq!(
+    ///     token_types,
+    ///     vec![
+    ///         Lexer::tIDENTIFIER,
+    ///         Lexer::tWHITESPACE,
+    ///         Lexer::tCOMMENT,
+    ///         Lexer::END_OF_INPUT
+    ///     ]
+    /// );
+    /// ```
+    pub record_trivia: bool,
+
+    /// When set to true the parser also emits warnings
+    /// that MRI reports only in verbose mode (`ruby -w`):
+    ///
+    /// 1. `assigned but unused variable` for local variables
+    ///    that are assigned but never read (names starting with `_`,
+    ///    method and block arguments and locals of scopes that call `binding`
+    ///    are not reported)
+    /// 2. `unused literal ignored` and `possibly useless use of ... in void context`
+    ///    for statements (other than the last one in a list) whose values are discarded
+    /// 3. `regex literal in condition` for regexes with interpolation
+    ///    and `range literal in condition` for ranges with literal ends
+    ///    (assignments of literals in conditions, string and static regex literals
+    ///    and ranges of `nil`, `true` and `false` used as conditions are always reported)
+    /// 4. duplicated literal `when` values of `case` and `in` clauses of `case ... in`
+    ///    that are unreachable because an earlier pattern matches any value
+    ///
+    /// # Example
+    /// ```
+    /// use lib_ruby_parser::{DiagnosticMessage, Parser, ParserOptions};
+    ///
+    /// let options = ParserOptions {
+    ///     verbose: true,
+    ///     ..Default::default()
+    /// };
+    /// let result = Parser::new(b"def m(a); b = a; _c = 1; end".to_vec(), options).do_parse();
+    /// let messages = result
+    ///     .diagnostics
+    ///     .into_iter()
+    ///     .map(|diagnostic| diagnostic.message)
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert_eq!(
+    ///     messages,
+    ///     vec![DiagnosticMessage::AssignedButUnusedVariable {
+    ///         name: String::from("b")
+    ///     }]
+    /// );
+    /// ```
+    pub verbose: bool,
+
+    /// When set to true the parser emits
+    /// `mismatched indentations at 'end' with '...' at N` warnings
+    /// for `end`s that are indented differently from their opening
+    /// `def`, `class`, `module`, `if`, `unless`, `while`, `until`,
+    /// `case`, `for`, `begin` or `do`. Like in MRI, an `end` or an opening
+    /// keyword that is not the first token on its line (e.g. `x = if` or `foo do`)
+    /// is never reported.
+    ///
+    /// When it's false these warnings are still emitted
+    /// after a `# warn_indent: true` magic comment (and until `# warn_indent: false`).
+    ///
+    /// # Example
+    /// ```
+    /// use lib_ruby_parser::{DiagnosticMessage, Parser, ParserOptions};
+    ///
+    /// let options = ParserOptions {
+    ///     warn_indent: true,
+    ///     ..Default::default()
+    /// };
+    /// let result = Parser::new(b"def m\n  end".to_vec(), options).do_parse();
+    /// let messages = result
+    ///     .diagnostics
+    ///     .into_iter()
+    ///     .map(|diagnostic| diagnostic.message)
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert_eq!(
+    ///     messages,
+    ///     vec![DiagnosticMessage::MismatchedIndentation {
+    ///         opening: String::from("def"),
+    ///         line: 1
+    ///     }]
+    /// );
+    /// ```
+    pub warn_indent: bool,
+
+    /// When set to true an "unexpected end of input" error gets a related
+    /// location of the block that is most likely not closed.
+    ///
+    /// The search re-parses indented blocks of the input,
+    /// so it's off by default.
+    ///
+    /// # Example
+    /// ```
+    /// use lib_ruby_parser::{Loc, Parser, ParserOptions};
+    ///
+    /// let options = ParserOptions {
+    ///     suggest_unclosed_block: true,
+    ///     ..Default::default()
+    /// };
+    /// let input = b"def foo\n  bar do\n    1\nend\n".to_vec();
+    /// let result = Parser::new(input, options).do_parse();
+    ///
+    /// assert_eq!(
+    ///     result.diagnostics[0].related,
+    ///     vec![(
+    ///         Loc { begin: 10, end: 16 },
+    ///         String::from("this block is probably not closed")
+    ///     )]
+    /// );
+    /// ```
+    pub suggest_unclosed_block: bool,
+
+    /// Optional handler that receives every diagnostic as soon as it's emitted.
+    ///
+    /// If it returns `DiagnosticAction::Stop` parsing stops
+    /// (at the latest when the next token is requested),
+    /// `ParserResult::aborted` is set to `true` and `ParserResult::ast` is `None`.
+    ///
+    /// Keep in mind that suppression comments are applied
+    /// only to `ParserResult::diagnostics`, so this handler
+    /// receives all diagnostics.
+    ///
+    /// # Example
+    /// ```
+    /// use lib_ruby_parser::{
+    ///     DiagnosticAction, DiagnosticHandler, Parser, ParserOptions, ParserResult,
+    /// };
+    /// let stop_on_error = DiagnosticHandler::new(Box::new(|diagnostic| {
+    ///     if diagnostic.is_error() {
+    ///         DiagnosticAction::Stop
+    ///     } else {
+    ///         DiagnosticAction::Continue
+    ///     }
+    /// }));
+    /// let options = ParserOptions {
+    ///     on_diagnostic: Some(stop_on_error),
+    ///     ..Default::default()
+    /// };
+    /// let ParserResult { aborted, diagnostics, .. } =
+    ///     Parser::new(b"def m(a, a); end; def m(b, b); end".to_vec(), options).do_parse();
+    ///
+    /// assert!(aborted);
+    /// assert_eq!(diagnostics.len(), 1);
+    /// ```
+    pub on_diagnostic: Option<DiagnosticHandler>,
+
+    /// Optional tracer that receives structured events
+    /// from the lexer and the parser (lexed tokens, lex state changes,
+    /// shifts, reductions and context flag changes).
+    ///
+    /// When it's `None` no events are constructed at all.
+    ///
+    /// # Example
+    /// ```
+    /// use lib_ruby_parser::{Parser, ParserOptions, TraceEvent, Tracer};
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    ///
+    /// let reductions = Rc::new(RefCell::new(vec![]));
+    /// let tracer = {
+    ///     let reductions = Rc::clone(&reductions);
+    ///     Tracer::new(Box::new(move |event| {
+    ///         if let TraceEvent::Reduce { rule } = event {
+    ///             reductions.borrow_mut().push(rule.to_string());
+    ///         }
+    ///     }))
+    /// };
+    /// let options = ParserOptions {
+    ///     tracer: Some(tracer),
+    ///     ..Default::default()
+    /// };
+    /// Parser::new(b"42".to_vec(), options).do_parse();
+    ///
+    /// assert_eq!(
+    ///     reductions.borrow().last().map(String::as_str),
+    ///     Some("program: $@1 top_compstmt")
+    /// );
+    /// ```
+    pub tracer: Option<Tracer>,
 }
 
 const DEFAULT_BUFFER_NAME: &str = "(eval)";
@@ -96,6 +305,12 @@ impl Default for ParserOptions {
             decoder: None,
             token_rewriter: None,
             record_tokens: true,
+            record_trivia: false,
+            verbose: false,
+            warn_indent: false,
+            suggest_unclosed_block: false,
+            on_diagnostic: None,
+            tracer: None,
         }
     }
 }
@@ -36,6 +36,12 @@ pub struct ParserResult {
     /// Pass **this** data to `Loc::source`, otherwise you'll get
     /// incorrect source ranges.
     pub input: DecodedInput,
+
+    /// `true` if parsing has been stopped by `ParserOptions::on_diagnostic`.
+    ///
+    /// In such case `ast` is `None` and `diagnostics` end with
+    /// the diagnostic that caused it.
+    pub aborted: bool,
 }
 
 impl std::fmt::Debug for ParserResult {
@@ -46,6 +52,7 @@ impl std::fmt::Debug for ParserResult {
             .field("diagnostics", &self.diagnostics)
             .field("comments", &self.comments)
             .field("magic_comments", &self.magic_comments)
+            .field("aborted", &self.aborted)
             .finish()
     }
 }
@@ -61,10 +68,11 @@ fn test_fmt() {
                 diagnostics: vec![],
                 comments: vec![],
                 magic_comments: vec![],
-                input: DecodedInput::default()
+                input: DecodedInput::default(),
+                aborted: false,
             }
         ),
         // All fields except `input`
-        "ParserResult { ast: None, tokens: [], diagnostics: [], comments: [], magic_comments: [] }"
+        "ParserResult { ast: None, tokens: [], diagnostics: [], comments: [], magic_comments: [], aborted: false }"
     )
 }
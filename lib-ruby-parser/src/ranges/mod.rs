@@ -0,0 +1,273 @@
+use crate::nodes::*;
+use crate::source::{line_of, CommentType, DecodedInput};
+use crate::{Loc, Node, ParserResult};
+
+/// Kind of a folding range
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub enum FoldingRangeKind {
+    /// `=begin`/`=end` comment or consecutive lines of `#` comments
+    Comment,
+
+    /// Class, module, method, block, heredoc, multi-line array or hash
+    Region,
+}
+
+/// LSP-style folding range, lines are 0-based.
+///
+/// `end_line` is the last line that gets hidden,
+/// so a line that starts with the closing `end`/`}`/`]`
+/// (or a heredoc terminator) remains visible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct FoldingRange {
+    /// Line that remains visible when the range is folded
+    pub start_line: usize,
+
+    /// Last folded line
+    pub end_line: usize,
+
+    /// Kind of the range
+    pub kind: FoldingRangeKind,
+}
+
+/// LSP-style position, both line and character are 0-based,
+/// character is measured in UTF-16 code units
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct Position {
+    /// Line of the position
+    pub line: usize,
+
+    /// Column of the position
+    pub character: usize,
+}
+
+/// A range returned by `selection_ranges`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct SelectionRange {
+    /// Location of the range
+    pub loc: Loc,
+
+    /// Position of `loc.begin`
+    pub start: Position,
+
+    /// Position of `loc.end`
+    pub end: Position,
+}
+
+/// Returns folding ranges of classes, modules, methods, blocks, heredocs,
+/// multi-line arrays and hashes, `=begin` comments
+/// and runs of 2 or more lines that start with `#` comments.
+///
+/// Ranges are sorted by `start_line`, if multiple ranges start
+/// on the same line only the longest one is returned.
+///
+/// # Example
+/// ```
+/// use lib_ruby_parser::ranges::{folding_ranges, FoldingRange, FoldingRangeKind};
+/// use lib_ruby_parser::{Parser, ParserOptions};
+///
+/// let src = "# a\n# b\ndef foo\n  bar\nend";
+/// let result = Parser::new(src, ParserOptions::default()).do_parse();
+/// assert_eq!(
+///     folding_ranges(&result),
+///     vec![
+///         FoldingRange { start_line: 0, end_line: 1, kind: FoldingRangeKind::Comment },
+///         FoldingRange { start_line: 2, end_line: 3, kind: FoldingRangeKind::Region },
+///     ]
+/// );
+/// ```
+pub fn folding_ranges(result: &ParserResult) -> Vec<FoldingRange> {
+    let input = &result.input;
+    let mut ranges = vec![];
+    if let Some(ast) = result.ast.as_deref() {
+        collect_node_ranges(ast, input, &mut ranges);
+    }
+    collect_comment_ranges(result, &mut ranges);
+
+    ranges.sort_by_key(|range| (range.start_line, std::cmp::Reverse(range.end_line)));
+    ranges.dedup_by_key(|range| range.start_line);
+    ranges
+}
+
+/// Returns ranges that can be selected by expanding selection
+/// from a given byte offset: the innermost node that covers it,
+/// then its enclosing statement, body, method, class and so on
+/// up to the whole program.
+///
+/// Each range strictly contains the previous one.
+///
+/// # Example
+/// ```
+/// use lib_ruby_parser::ranges::selection_ranges;
+/// use lib_ruby_parser::{Parser, ParserOptions};
+///
+/// let src = "def foo\n  bar(1 + 2)\n  baz\nend";
+/// let result = Parser::new(src, ParserOptions::default()).do_parse();
+/// let sources = selection_ranges(&result, 14)
+///     .into_iter()
+///     .map(|range| range.loc.source(&result.input).unwrap())
+///     .collect::<Vec<_>>();
+/// assert_eq!(
+///     sources,
+///     vec!["1", "1 + 2", "bar(1 + 2)", "bar(1 + 2)\n  baz", src]
+/// );
+/// ```
+pub fn selection_ranges(result: &ParserResult, offset: usize) -> Vec<SelectionRange> {
+    let path = match result.ast.as_deref().and_then(|ast| ast.path_to(offset)) {
+        Some(path) => path,
+        None => return vec![],
+    };
+
+    let mut locs: Vec<Loc> = vec![];
+    let nodes = std::iter::once(path.node).chain(path.ancestors.iter().rev().map(|a| a.node));
+    for node in nodes {
+        let loc = *node.expression();
+        // heredoc bodies are not a part of the enclosing expression
+        let contains_last = locs
+            .last()
+            .map_or(true, |last| loc.begin <= last.begin && last.end <= loc.end);
+        if contains_last && locs.last() != Some(&loc) {
+            locs.push(loc);
+        }
+    }
+
+    let input = &result.input;
+    locs.into_iter()
+        .map(|loc| SelectionRange {
+            loc,
+            start: position(input, loc.begin),
+            end: position(input, loc.end),
+        })
+        .collect()
+}
+
+fn position(input: &DecodedInput, pos: usize) -> Position {
+    let line = line_of(&input.lines, pos);
+    let start = input.lines.get(line).map_or(0, |line| line.start).min(pos);
+    let character = String::from_utf8_lossy(&input.as_shared_bytes()[start..pos])
+        .encode_utf16()
+        .count();
+    Position { line, character }
+}
+
+fn starts_line(input: &DecodedInput, pos: usize) -> bool {
+    let line = &input.lines[line_of(&input.lines, pos)];
+    input.as_shared_bytes()[line.start..pos]
+        .iter()
+        .all(|byte| byte.is_ascii_whitespace())
+}
+
+fn push_region(input: &DecodedInput, open: &Loc, close: &Loc, ranges: &mut Vec<FoldingRange>) {
+    let start_line = line_of(&input.lines, open.begin);
+    let close_line = line_of(&input.lines, close.begin);
+    let end_line = if starts_line(input, close.begin) {
+        close_line.saturating_sub(1)
+    } else {
+        close_line
+    };
+    if end_line > start_line {
+        ranges.push(FoldingRange {
+            start_line,
+            end_line,
+            kind: FoldingRangeKind::Region,
+        })
+    }
+}
+
+fn collect_node_ranges(node: &Node, input: &DecodedInput, ranges: &mut Vec<FoldingRange>) {
+    match node {
+        Node::Class(Class {
+            keyword_l, end_l, ..
+        })
+        | Node::SClass(SClass {
+            keyword_l, end_l, ..
+        })
+        | Node::Module(Module {
+            keyword_l, end_l, ..
+        }) => push_region(input, keyword_l, end_l, ranges),
+        Node::Def(Def {
+            keyword_l,
+            end_l: Some(end_l),
+            ..
+        })
+        | Node::Defs(Defs {
+            keyword_l,
+            end_l: Some(end_l),
+            ..
+        }) => push_region(input, keyword_l, end_l, ranges),
+        Node::Block(Block { begin_l, end_l, .. })
+        | Node::Numblock(Numblock { begin_l, end_l, .. })
+        | Node::Array(Array {
+            begin_l: Some(begin_l),
+            end_l: Some(end_l),
+            ..
+        })
+        | Node::Hash(Hash {
+            begin_l: Some(begin_l),
+            end_l: Some(end_l),
+            ..
+        }) => push_region(input, begin_l, end_l, ranges),
+        Node::Heredoc(Heredoc {
+            expression_l,
+            heredoc_end_l,
+            ..
+        })
+        | Node::XHeredoc(XHeredoc {
+            expression_l,
+            heredoc_end_l,
+            ..
+        }) => push_region(input, expression_l, heredoc_end_l, ranges),
+        _ => {}
+    }
+
+    for child in node.children() {
+        collect_node_ranges(child, input, ranges);
+    }
+}
+
+fn collect_comment_ranges(result: &ParserResult, ranges: &mut Vec<FoldingRange>) {
+    let input = &result.input;
+    // current run of `#` comments that start their lines
+    let mut run: Option<(usize, usize)> = None;
+    let flush = |run: &mut Option<(usize, usize)>, ranges: &mut Vec<FoldingRange>| {
+        if let Some((start_line, end_line)) = run.take() {
+            if end_line > start_line {
+                ranges.push(FoldingRange {
+                    start_line,
+                    end_line,
+                    kind: FoldingRangeKind::Comment,
+                })
+            }
+        }
+    };
+
+    for comment in result.comments.iter() {
+        let loc = &comment.location;
+        let start_line = line_of(&input.lines, loc.begin);
+        match comment.kind {
+            CommentType::Inline if starts_line(input, loc.begin) => match run {
+                Some((_, ref mut end_line)) if *end_line + 1 == start_line => {
+                    *end_line = start_line;
+                }
+                _ => {
+                    flush(&mut run, ranges);
+                    run = Some((start_line, start_line));
+                }
+            },
+            CommentType::Document => {
+                flush(&mut run, ranges);
+                let end_line = line_of(&input.lines, loc.end.saturating_sub(1).max(loc.begin));
+                run = Some((start_line, end_line));
+                flush(&mut run, ranges);
+            }
+            _ => flush(&mut run, ranges),
+        }
+    }
+    flush(&mut run, ranges);
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,110 @@
+use crate::ranges::{
+    folding_ranges, selection_ranges, FoldingRange, FoldingRangeKind, Position, SelectionRange,
+};
+use crate::{Loc, Parser, ParserOptions, ParserResult};
+
+fn parse(src: &str) -> ParserResult {
+    Parser::new(src, ParserOptions::default()).do_parse()
+}
+
+fn region(start_line: usize, end_line: usize) -> FoldingRange {
+    FoldingRange {
+        start_line,
+        end_line,
+        kind: FoldingRangeKind::Region,
+    }
+}
+
+fn comment(start_line: usize, end_line: usize) -> FoldingRange {
+    FoldingRange {
+        start_line,
+        end_line,
+        kind: FoldingRangeKind::Comment,
+    }
+}
+
+#[test]
+fn test_folding_ranges_of_blocks() {
+    let result = parse(
+        "class A\n  # one\n  # two\n  def foo\n    [1,\n     2].each do |x|\n      x\n    end\n  end\nend",
+    );
+
+    assert_eq!(
+        folding_ranges(&result),
+        vec![
+            region(0, 8),
+            comment(1, 2),
+            region(3, 7),
+            region(4, 5),
+            region(5, 6)
+        ]
+    );
+}
+
+#[test]
+fn test_folding_ranges_of_literals_and_comments() {
+    let result =
+        parse("=begin\ndoc\n=end\nx = <<~E\n  a\n  b\nE\nh = { a: 1,\n  b: 2 }\nfoo # c\n# d\n");
+
+    assert_eq!(
+        folding_ranges(&result),
+        vec![comment(0, 2), region(3, 5), region(7, 8)]
+    );
+}
+
+#[test]
+fn test_folding_ranges_on_the_same_line() {
+    let result = parse("a = [{\n  b: 1\n}]");
+
+    assert_eq!(folding_ranges(&result), vec![region(0, 2)]);
+}
+
+#[test]
+fn test_selection_ranges() {
+    let result = parse("x = \"ä\"; foo(x)");
+
+    assert_eq!(
+        selection_ranges(&result, 14),
+        vec![
+            SelectionRange {
+                loc: Loc { begin: 14, end: 15 },
+                start: Position {
+                    line: 0,
+                    character: 13
+                },
+                end: Position {
+                    line: 0,
+                    character: 14
+                },
+            },
+            SelectionRange {
+                loc: Loc { begin: 10, end: 16 },
+                start: Position {
+                    line: 0,
+                    character: 9
+                },
+                end: Position {
+                    line: 0,
+                    character: 15
+                },
+            },
+            SelectionRange {
+                loc: Loc { begin: 0, end: 16 },
+                start: Position {
+                    line: 0,
+                    character: 0
+                },
+                end: Position {
+                    line: 0,
+                    character: 15
+                },
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_selection_ranges_outside_of_ast() {
+    assert_eq!(selection_ranges(&parse(""), 0), vec![]);
+    assert_eq!(selection_ranges(&parse("foo  "), 4), vec![]);
+}
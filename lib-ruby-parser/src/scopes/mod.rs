@@ -0,0 +1,202 @@
+mod resolver;
+
+use crate::{Loc, ParserResult};
+
+/// Kind of a `Scope`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub enum ScopeKind {
+    /// Top level of the file
+    TopLevel,
+
+    /// Body of `class A`
+    Class,
+
+    /// Body of `module A`
+    Module,
+
+    /// Body of `class << self`
+    SingletonClass,
+
+    /// Arguments and body of `def foo` or `def self.foo`
+    Def,
+
+    /// Arguments and body of a `do ... end`/`{ ... }` block
+    Block,
+
+    /// Arguments and body of `-> {}`
+    Lambda,
+}
+
+impl ScopeKind {
+    /// Returns `true` if a scope of this kind can see local variables of its parent scope
+    pub fn inherits_locals(&self) -> bool {
+        matches!(self, Self::Block | Self::Lambda)
+    }
+}
+
+/// Lexical scope of local variables
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scope {
+    /// Kind of the scope
+    pub kind: ScopeKind,
+
+    /// Location of the code that belongs to the scope
+    pub loc: Loc,
+
+    /// Index of the parent scope in `ScopeTree::scopes`, `None` for the top level
+    pub parent: Option<usize>,
+
+    /// Indexes of variables declared in this scope in `ScopeTree::variables`
+    pub variables: Vec<usize>,
+}
+
+/// Kind of a `Definition`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub enum DefinitionKind {
+    /// `a = 1`, `a += 1`, `a, b = 1, 2`, `for a in b`
+    Assignment,
+
+    /// `def foo(a, b = 1, *c, d:, e: 1, **f, &g)`, `proc { |a; b| }`
+    Argument,
+
+    /// `1 in a`, `case 1; in [a]; end`
+    PatternMatch,
+
+    /// `/(?<a>.)/ =~ b`
+    RegexpCapture,
+}
+
+/// A place where a local variable gets assigned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Definition {
+    /// Location of the variable name
+    pub loc: Loc,
+
+    /// Kind of the definition
+    pub kind: DefinitionKind,
+}
+
+/// Local variable with all its definitions and references
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Variable {
+    /// Name of the variable
+    pub name: String,
+
+    /// Index of the scope that declares the variable in `ScopeTree::scopes`
+    pub scope: usize,
+
+    /// Definitions in the order of the source code
+    pub definitions: Vec<Definition>,
+
+    /// Locations of reads (`Lvar` nodes) in the order of the source code
+    pub references: Vec<Loc>,
+}
+
+impl Variable {
+    /// Returns sorted locations of all definitions and references,
+    /// i.e. everything that has to be renamed together
+    pub fn locations(&self) -> Vec<Loc> {
+        let mut locs = self
+            .definitions
+            .iter()
+            .map(|definition| definition.loc)
+            .chain(self.references.iter().copied())
+            .collect::<Vec<_>>();
+        locs.sort_by_key(|loc| loc.begin);
+        locs
+    }
+
+    fn first_definition(&self) -> Option<usize> {
+        self.definitions
+            .iter()
+            .map(|definition| definition.loc.begin)
+            .min()
+    }
+}
+
+/// Tree of lexical scopes with their local variables
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScopeTree {
+    /// All scopes, parents go before their children, the first one is the top level
+    pub scopes: Vec<Scope>,
+
+    /// All local variables
+    pub variables: Vec<Variable>,
+}
+
+impl ScopeTree {
+    /// Builds a tree of scopes from the AST of a given `ParserResult`
+    /// and binds every local variable read to its declaration.
+    ///
+    /// # Example
+    /// ```
+    /// use lib_ruby_parser::scopes::ScopeTree;
+    /// use lib_ruby_parser::{Loc, Parser, ParserOptions};
+    ///
+    /// let src = "a = 1\n[2].each { |b| a + b }";
+    /// let result = Parser::new(src, ParserOptions::default()).do_parse();
+    /// let tree = ScopeTree::new(&result);
+    ///
+    /// let a = tree.variable_at(21).unwrap();
+    /// assert_eq!(a.name, "a");
+    /// assert_eq!(a.locations(), vec![Loc { begin: 0, end: 1 }, Loc { begin: 21, end: 22 }]);
+    ///
+    /// let visible = tree.locals_at(23).into_iter().map(|v| v.name.as_str()).collect::<Vec<_>>();
+    /// assert_eq!(visible, vec!["b", "a"]);
+    /// ```
+    pub fn new(result: &ParserResult) -> Self {
+        resolver::Resolver::run(result)
+    }
+
+    /// Returns index of the innermost scope that contains a given byte offset
+    pub fn scope_at(&self, offset: usize) -> usize {
+        // parents go first, so the last matching scope is the innermost one
+        self.scopes
+            .iter()
+            .rposition(|scope| scope.loc.begin <= offset && offset < scope.loc.end)
+            .unwrap_or(0)
+    }
+
+    /// Returns local variables that are visible at a given byte offset
+    /// (i.e. defined before it in the current scope or in scopes it inherits locals from),
+    /// innermost scopes go first, shadowed variables are not returned.
+    pub fn locals_at(&self, offset: usize) -> Vec<&Variable> {
+        let mut result: Vec<&Variable> = vec![];
+        let mut scope_idx = Some(self.scope_at(offset));
+        while let Some(idx) = scope_idx {
+            let scope = &self.scopes[idx];
+            for variable in scope.variables.iter().map(|idx| &self.variables[*idx]) {
+                let defined = variable
+                    .first_definition()
+                    .map_or(false, |begin| begin < offset);
+                if defined && result.iter().all(|v| v.name != variable.name) {
+                    result.push(variable)
+                }
+            }
+            scope_idx = if scope.kind.inherits_locals() {
+                scope.parent
+            } else {
+                None
+            };
+        }
+        result
+    }
+
+    /// Returns a variable that is defined or referenced at a given byte offset
+    pub fn variable_at(&self, offset: usize) -> Option<&Variable> {
+        let contains = |loc: &Loc| loc.begin <= offset && offset < loc.end;
+
+        self.variables.iter().find(|variable| {
+            variable
+                .definitions
+                .iter()
+                .any(|definition| contains(&definition.loc))
+                || variable.references.iter().any(contains)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests;
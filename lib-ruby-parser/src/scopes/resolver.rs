@@ -0,0 +1,321 @@
+use crate::nodes::*;
+use crate::scopes::{Definition, DefinitionKind, Scope, ScopeKind, ScopeTree, Variable};
+use crate::source::DecodedInput;
+use crate::traverse::visitor::*;
+use crate::{Loc, ParserResult};
+
+#[derive(Debug)]
+pub(crate) struct Resolver<'a> {
+    input: &'a DecodedInput,
+    tree: ScopeTree,
+    current: usize,
+}
+
+impl<'a> Resolver<'a> {
+    pub(crate) fn run(result: &'a ParserResult) -> ScopeTree {
+        let top_level = Scope {
+            kind: ScopeKind::TopLevel,
+            loc: Loc {
+                begin: 0,
+                end: result.input.as_shared_bytes().len(),
+            },
+            parent: None,
+            variables: vec![],
+        };
+        let mut this = Self {
+            input: &result.input,
+            tree: ScopeTree {
+                scopes: vec![top_level],
+                variables: vec![],
+            },
+            current: 0,
+        };
+        if let Some(ast) = result.ast.as_deref() {
+            this.visit(ast);
+        }
+        this.tree
+    }
+
+    fn with_scope<F>(&mut self, kind: ScopeKind, loc: Loc, f: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        let parent = self.current;
+        self.tree.scopes.push(Scope {
+            kind,
+            loc,
+            parent: Some(parent),
+            variables: vec![],
+        });
+        self.current = self.tree.scopes.len() - 1;
+        f(self);
+        self.current = parent;
+    }
+
+    fn lookup(&self, name: &str) -> Option<usize> {
+        let mut scope_idx = Some(self.current);
+        while let Some(idx) = scope_idx {
+            let scope = &self.tree.scopes[idx];
+            let found = scope
+                .variables
+                .iter()
+                .copied()
+                .find(|var_idx| self.tree.variables[*var_idx].name == name);
+            if found.is_some() {
+                return found;
+            }
+            scope_idx = if scope.kind.inherits_locals() {
+                scope.parent
+            } else {
+                None
+            };
+        }
+        None
+    }
+
+    fn declare_in_current_scope(&mut self, name: &str) -> usize {
+        let var_idx = self.tree.variables.len();
+        self.tree.variables.push(Variable {
+            name: name.to_string(),
+            scope: self.current,
+            definitions: vec![],
+            references: vec![],
+        });
+        self.tree.scopes[self.current].variables.push(var_idx);
+        var_idx
+    }
+
+    // Arguments always declare a new variable (and shadow outer ones),
+    // other definitions re-assign a visible variable with the same name
+    fn define(&mut self, name: &str, loc: Loc, kind: DefinitionKind) {
+        let var_idx = match kind {
+            DefinitionKind::Argument => None,
+            _ => self.lookup(name),
+        };
+        let var_idx = var_idx.unwrap_or_else(|| self.declare_in_current_scope(name));
+        self.tree.variables[var_idx]
+            .definitions
+            .push(Definition { loc, kind });
+    }
+
+    fn define_maybe(&mut self, name: &Option<String>, loc: &Option<Loc>) {
+        if let (Some(name), Some(loc)) = (name, loc) {
+            self.define(name, *loc, DefinitionKind::Argument)
+        }
+    }
+
+    fn reference(&mut self, name: &str, loc: Loc) {
+        // numbered parameters (and reads of variables introduced by `eval`)
+        // are not declared anywhere
+        let var_idx = self
+            .lookup(name)
+            .unwrap_or_else(|| self.declare_in_current_scope(name));
+        self.tree.variables[var_idx].references.push(loc);
+    }
+
+    fn define_regexp_captures(&mut self, re: &Node) {
+        let parts = match re {
+            Node::Regexp(Regexp { parts, .. }) => parts,
+            _ => return,
+        };
+        for part in parts {
+            if let Node::Str(Str { expression_l, .. }) = part {
+                for (name, loc) in named_captures(self.input, expression_l) {
+                    self.define(&name, loc, DefinitionKind::RegexpCapture)
+                }
+            }
+        }
+    }
+}
+
+// Returns names and locations of `(?<name>...)` and `(?'name'...)` groups
+fn named_captures(input: &DecodedInput, loc: &Loc) -> Vec<(String, Loc)> {
+    let bytes = input.as_shared_bytes();
+    let source = match bytes.get(loc.begin..loc.end) {
+        Some(source) => source,
+        None => return vec![],
+    };
+
+    let mut result = vec![];
+    let mut idx = 0;
+    while idx < source.len() {
+        match source[idx..] {
+            [b'\\', ..] => idx += 2,
+            [b'(', b'?', open @ (b'<' | b'\''), ..] => {
+                let close = if open == b'<' { b'>' } else { b'\'' };
+                let name_begin = idx + 3;
+                let name_len = source[name_begin..]
+                    .iter()
+                    .take_while(|byte| byte.is_ascii_alphanumeric() || **byte == b'_')
+                    .count();
+                let name_end = name_begin + name_len;
+                if name_len > 0 && source.get(name_end) == Some(&close) {
+                    result.push((
+                        String::from_utf8_lossy(&source[name_begin..name_end]).into_owned(),
+                        Loc {
+                            begin: loc.begin + name_begin,
+                            end: loc.begin + name_end,
+                        },
+                    ));
+                }
+                idx = name_end.max(idx + 1);
+            }
+            _ => idx += 1,
+        }
+    }
+    result
+}
+
+impl Visitor for Resolver<'_> {
+    fn on_def(&mut self, node: &Def) {
+        self.with_scope(ScopeKind::Def, node.expression_l, |this| {
+            visit_def(this, node)
+        });
+    }
+
+    fn on_defs(&mut self, node: &Defs) {
+        self.visit(&node.definee);
+        self.with_scope(ScopeKind::Def, node.expression_l, |this| {
+            if let Some(args) = node.args.as_deref() {
+                this.visit(args);
+            }
+            if let Some(body) = node.body.as_deref() {
+                this.visit(body);
+            }
+        });
+    }
+
+    fn on_class(&mut self, node: &Class) {
+        self.visit(&node.name);
+        if let Some(superclass) = node.superclass.as_deref() {
+            self.visit(superclass);
+        }
+        self.with_scope(ScopeKind::Class, node.expression_l, |this| {
+            if let Some(body) = node.body.as_deref() {
+                this.visit(body);
+            }
+        });
+    }
+
+    fn on_module(&mut self, node: &Module) {
+        self.visit(&node.name);
+        self.with_scope(ScopeKind::Module, node.expression_l, |this| {
+            if let Some(body) = node.body.as_deref() {
+                this.visit(body);
+            }
+        });
+    }
+
+    fn on_s_class(&mut self, node: &SClass) {
+        self.visit(&node.expr);
+        self.with_scope(ScopeKind::SingletonClass, node.expression_l, |this| {
+            if let Some(body) = node.body.as_deref() {
+                this.visit(body);
+            }
+        });
+    }
+
+    fn on_block(&mut self, node: &Block) {
+        self.visit(&node.call);
+        let (kind, begin) = match &*node.call {
+            Node::Lambda(Lambda { expression_l }) => (ScopeKind::Lambda, expression_l.begin),
+            _ => (ScopeKind::Block, node.begin_l.begin),
+        };
+        let loc = Loc {
+            begin,
+            end: node.end_l.end,
+        };
+        self.with_scope(kind, loc, |this| {
+            if let Some(args) = node.args.as_deref() {
+                this.visit(args);
+            }
+            if let Some(body) = node.body.as_deref() {
+                this.visit(body);
+            }
+        });
+    }
+
+    fn on_numblock(&mut self, node: &Numblock) {
+        self.visit(&node.call);
+        let (kind, begin) = match &*node.call {
+            Node::Lambda(Lambda { expression_l }) => (ScopeKind::Lambda, expression_l.begin),
+            _ => (ScopeKind::Block, node.begin_l.begin),
+        };
+        let loc = Loc {
+            begin,
+            end: node.end_l.end,
+        };
+        self.with_scope(kind, loc, |this| this.visit(&node.body));
+    }
+
+    fn on_lvar(&mut self, node: &Lvar) {
+        self.reference(&node.name, node.expression_l);
+    }
+
+    fn on_lvasgn(&mut self, node: &Lvasgn) {
+        // `a = a` reads the variable that is being assigned
+        self.define(&node.name, node.name_l, DefinitionKind::Assignment);
+        visit_lvasgn(self, node);
+    }
+
+    fn on_match_var(&mut self, node: &MatchVar) {
+        self.define(&node.name, node.name_l, DefinitionKind::PatternMatch);
+    }
+
+    fn on_match_with_lvasgn(&mut self, node: &MatchWithLvasgn) {
+        visit_match_with_lvasgn(self, node);
+        self.define_regexp_captures(&node.re);
+    }
+
+    fn on_arg(&mut self, node: &Arg) {
+        self.define(&node.name, node.expression_l, DefinitionKind::Argument);
+    }
+
+    fn on_optarg(&mut self, node: &Optarg) {
+        self.define(&node.name, node.name_l, DefinitionKind::Argument);
+        visit_optarg(self, node);
+    }
+
+    fn on_restarg(&mut self, node: &Restarg) {
+        self.define_maybe(&node.name, &node.name_l);
+    }
+
+    fn on_kwarg(&mut self, node: &Kwarg) {
+        self.define(&node.name, node.name_l, DefinitionKind::Argument);
+    }
+
+    fn on_kwoptarg(&mut self, node: &Kwoptarg) {
+        self.define(&node.name, node.name_l, DefinitionKind::Argument);
+        visit_kwoptarg(self, node);
+    }
+
+    fn on_kwrestarg(&mut self, node: &Kwrestarg) {
+        self.define_maybe(&node.name, &node.name_l);
+    }
+
+    fn on_blockarg(&mut self, node: &Blockarg) {
+        self.define_maybe(&node.name, &node.name_l);
+    }
+
+    fn on_shadowarg(&mut self, node: &Shadowarg) {
+        self.define(&node.name, node.expression_l, DefinitionKind::Argument);
+    }
+}
+
+#[test]
+fn test_named_captures() {
+    let mut input = DecodedInput::named("(named_captures_test)");
+    input.update_bytes(br"(?<a>.)\(?<b>(?'c_1'x)(?<=y)(?<>)".to_vec());
+    let loc = Loc {
+        begin: 0,
+        end: input.as_shared_bytes().len(),
+    };
+
+    assert_eq!(
+        named_captures(&input, &loc),
+        vec![
+            ("a".to_string(), Loc { begin: 3, end: 4 }),
+            ("c_1".to_string(), Loc { begin: 16, end: 19 }),
+        ]
+    );
+}
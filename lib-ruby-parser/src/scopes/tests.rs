@@ -0,0 +1,112 @@
+use crate::scopes::{DefinitionKind, ScopeKind, ScopeTree};
+use crate::{Loc, Parser, ParserOptions};
+
+fn build(src: &str) -> ScopeTree {
+    let result = Parser::new(src, ParserOptions::default()).do_parse();
+    ScopeTree::new(&result)
+}
+
+fn names_in_scope(tree: &ScopeTree, scope_idx: usize) -> Vec<&str> {
+    tree.scopes[scope_idx]
+        .variables
+        .iter()
+        .map(|idx| tree.variables[*idx].name.as_str())
+        .collect()
+}
+
+#[test]
+fn test_scope_tree() {
+    let tree = build(
+        "x = 1\nclass A\n  y = 2\n  def foo(a, b = 1, *c, d:, **e, &f)\n    g = a\n  end\nend\n-> (h) { [1].each { |i; j| h } }",
+    );
+
+    assert_eq!(
+        tree.scopes
+            .iter()
+            .map(|scope| (scope.kind, scope.parent))
+            .collect::<Vec<_>>(),
+        vec![
+            (ScopeKind::TopLevel, None),
+            (ScopeKind::Class, Some(0)),
+            (ScopeKind::Def, Some(1)),
+            (ScopeKind::Lambda, Some(0)),
+            (ScopeKind::Block, Some(3)),
+        ]
+    );
+    assert_eq!(names_in_scope(&tree, 0), vec!["x"]);
+    assert_eq!(names_in_scope(&tree, 1), vec!["y"]);
+    assert_eq!(
+        names_in_scope(&tree, 2),
+        vec!["a", "b", "c", "d", "e", "f", "g"]
+    );
+    assert_eq!(names_in_scope(&tree, 3), vec!["h"]);
+    assert_eq!(names_in_scope(&tree, 4), vec!["i", "j"]);
+
+    let h = &tree.variables[tree.scopes[3].variables[0]];
+    assert_eq!(h.references.len(), 1);
+}
+
+#[test]
+fn test_shadowing_and_reassignment() {
+    let src = "a = 1\nproc { |a| a }\nproc { a = 2 }\ndef foo\n  a = 3\nend\na";
+    let tree = build(src);
+
+    let outer = tree.variable_at(0).unwrap();
+    assert_eq!(
+        outer.locations(),
+        vec![
+            Loc { begin: 0, end: 1 },
+            Loc { begin: 28, end: 29 },
+            Loc { begin: 56, end: 57 },
+        ]
+    );
+
+    let block_arg = tree.variable_at(14).unwrap();
+    assert_eq!(block_arg.definitions[0].kind, DefinitionKind::Argument);
+    assert_eq!(
+        block_arg.locations(),
+        vec![Loc { begin: 14, end: 15 }, Loc { begin: 17, end: 18 }]
+    );
+
+    let method_local = tree.variable_at(46).unwrap();
+    assert_eq!(method_local.locations(), vec![Loc { begin: 46, end: 47 }]);
+}
+
+#[test]
+fn test_pattern_matching() {
+    let tree = build("case 1\nin [x, *y]\n  x\nend");
+
+    let x = tree.variable_at(11).unwrap();
+    assert_eq!(x.definitions[0].kind, DefinitionKind::PatternMatch);
+    assert_eq!(x.references, vec![Loc { begin: 20, end: 21 }]);
+    assert_eq!(tree.variable_at(15).unwrap().name, "y");
+}
+
+#[cfg(feature = "onig")]
+#[test]
+fn test_regexp_captures() {
+    let tree = build("/(?<z>.)/ =~ 's'\nz");
+
+    let z = tree.variable_at(4).unwrap();
+    assert_eq!(z.definitions[0].kind, DefinitionKind::RegexpCapture);
+    assert_eq!(z.references, vec![Loc { begin: 17, end: 18 }]);
+}
+
+#[test]
+fn test_locals_at() {
+    let src = "a = 1\ndef foo(b)\n  c = 2\n  [1].each { |d| e = d }\nend";
+    let tree = build(src);
+    let locals_at = |offset: usize| {
+        tree.locals_at(offset)
+            .into_iter()
+            .map(|variable| variable.name.as_str())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(locals_at(3), vec!["a"]);
+    // inside of `def`, `a` is not visible
+    assert_eq!(locals_at(19), vec!["b"]);
+    assert_eq!(locals_at(24), vec!["b", "c"]);
+    // inside of the block
+    assert_eq!(locals_at(46), vec!["d", "e", "b", "c"]);
+}
@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+
+use crate::nodes::*;
+use crate::semantic_tokens::{SemanticTokenModifiers, SemanticTokenType};
+use crate::traverse::visitor::*;
+use crate::Loc;
+
+/// Classification of identifiers that can be made only with AST,
+/// keyed by the beginning of the identifier
+#[derive(Debug, Default)]
+pub(crate) struct AstInfo {
+    names: HashMap<usize, (SemanticTokenType, u32)>,
+}
+
+impl AstInfo {
+    pub(crate) fn new(ast: Option<&Node>) -> Self {
+        let mut info = Self::default();
+        if let Some(ast) = ast {
+            info.visit(ast);
+        }
+        info
+    }
+
+    pub(crate) fn get(&self, loc: &Loc) -> Option<(SemanticTokenType, u32)> {
+        self.names.get(&loc.begin).copied()
+    }
+
+    fn add(&mut self, loc: &Loc, token_type: SemanticTokenType) {
+        self.names.entry(loc.begin).or_insert((token_type, 0));
+    }
+
+    fn declare(&mut self, loc: &Loc, token_type: SemanticTokenType) {
+        self.names
+            .insert(loc.begin, (token_type, SemanticTokenModifiers::DECLARATION));
+    }
+
+    fn declare_maybe(&mut self, loc: &Option<Loc>, token_type: SemanticTokenType) {
+        if let Some(loc) = loc {
+            self.declare(loc, token_type)
+        }
+    }
+}
+
+impl Visitor for AstInfo {
+    fn on_lvar(&mut self, node: &Lvar) {
+        self.add(&node.expression_l, SemanticTokenType::LocalVariable);
+        visit_lvar(self, node);
+    }
+
+    fn on_lvasgn(&mut self, node: &Lvasgn) {
+        self.declare(&node.name_l, SemanticTokenType::LocalVariable);
+        visit_lvasgn(self, node);
+    }
+
+    fn on_match_var(&mut self, node: &MatchVar) {
+        self.declare(&node.name_l, SemanticTokenType::LocalVariable);
+        visit_match_var(self, node);
+    }
+
+    fn on_send(&mut self, node: &Send) {
+        if let Some(selector_l) = &node.selector_l {
+            self.add(selector_l, SemanticTokenType::Method);
+        }
+        visit_send(self, node);
+    }
+
+    fn on_c_send(&mut self, node: &CSend) {
+        if let Some(selector_l) = &node.selector_l {
+            self.add(selector_l, SemanticTokenType::Method);
+        }
+        visit_c_send(self, node);
+    }
+
+    fn on_def(&mut self, node: &Def) {
+        self.declare(&node.name_l, SemanticTokenType::Method);
+        visit_def(self, node);
+    }
+
+    fn on_defs(&mut self, node: &Defs) {
+        self.declare(&node.name_l, SemanticTokenType::Method);
+        visit_defs(self, node);
+    }
+
+    fn on_sym(&mut self, node: &Sym) {
+        self.add(&node.expression_l, SemanticTokenType::Symbol);
+        visit_sym(self, node);
+    }
+
+    fn on_arg(&mut self, node: &Arg) {
+        self.declare(&node.expression_l, SemanticTokenType::Parameter);
+        visit_arg(self, node);
+    }
+
+    fn on_optarg(&mut self, node: &Optarg) {
+        self.declare(&node.name_l, SemanticTokenType::Parameter);
+        visit_optarg(self, node);
+    }
+
+    fn on_restarg(&mut self, node: &Restarg) {
+        self.declare_maybe(&node.name_l, SemanticTokenType::Parameter);
+        visit_restarg(self, node);
+    }
+
+    fn on_kwarg(&mut self, node: &Kwarg) {
+        self.declare(&node.name_l, SemanticTokenType::Parameter);
+        visit_kwarg(self, node);
+    }
+
+    fn on_kwoptarg(&mut self, node: &Kwoptarg) {
+        self.declare(&node.name_l, SemanticTokenType::Parameter);
+        visit_kwoptarg(self, node);
+    }
+
+    fn on_kwrestarg(&mut self, node: &Kwrestarg) {
+        self.declare_maybe(&node.name_l, SemanticTokenType::Parameter);
+        visit_kwrestarg(self, node);
+    }
+
+    fn on_blockarg(&mut self, node: &Blockarg) {
+        self.declare_maybe(&node.name_l, SemanticTokenType::Parameter);
+        visit_blockarg(self, node);
+    }
+
+    fn on_shadowarg(&mut self, node: &Shadowarg) {
+        self.declare(&node.expression_l, SemanticTokenType::Parameter);
+        visit_shadowarg(self, node);
+    }
+}
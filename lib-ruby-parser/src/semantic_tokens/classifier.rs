@@ -0,0 +1,322 @@
+use crate::semantic_tokens::ast_info::AstInfo;
+use crate::semantic_tokens::{ClassifiedRange, SemanticTokenType};
+use crate::source::DecodedInput;
+use crate::{Lexer, Loc, ParserResult, Token};
+
+#[derive(Debug)]
+enum Frame {
+    Literal(Literal),
+    Interpolation,
+}
+
+#[derive(Debug)]
+struct Literal {
+    token_type: SemanticTokenType,
+    heredoc: bool,
+    interpolates: bool,
+    term: u8,
+}
+
+impl Literal {
+    fn new(token_type: SemanticTokenType, begin: &[u8]) -> Self {
+        if let Some(heredoc_id) = begin.strip_prefix(b"<<") {
+            let heredoc_id = heredoc_id
+                .strip_prefix(b"~")
+                .or_else(|| heredoc_id.strip_prefix(b"-"))
+                .unwrap_or(heredoc_id);
+            return Self {
+                token_type,
+                heredoc: true,
+                interpolates: !heredoc_id.starts_with(b"'"),
+                term: b'\n',
+            };
+        }
+
+        let interpolates = !matches!(
+            begin,
+            [b'\'', ..] | [b':', b'\'', ..] | [b'%', b'q' | b'w' | b'i' | b's', ..]
+        );
+        let term = match begin.last() {
+            Some(b'(') => b')',
+            Some(b'[') => b']',
+            Some(b'{') => b'}',
+            Some(b'<') => b'>',
+            Some(c) => *c,
+            None => b'"',
+        };
+
+        Self {
+            token_type,
+            heredoc: false,
+            interpolates,
+            term,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Classifier<'a> {
+    input: &'a DecodedInput,
+    ast_info: AstInfo,
+    stack: Vec<Frame>,
+    symbol_pending: bool,
+    ranges: Vec<ClassifiedRange>,
+}
+
+impl<'a> Classifier<'a> {
+    pub(crate) fn run(result: &'a ParserResult) -> Vec<ClassifiedRange> {
+        let mut this = Self {
+            input: &result.input,
+            ast_info: AstInfo::new(result.ast.as_deref()),
+            stack: vec![],
+            symbol_pending: false,
+            ranges: vec![],
+        };
+
+        for token in result.tokens.iter() {
+            this.on_token(token);
+        }
+        for comment in result.comments.iter() {
+            this.push(comment.location, SemanticTokenType::Comment, 0);
+        }
+
+        let mut ranges = this.ranges;
+        ranges.sort_by_key(|range| (range.loc.begin, range.loc.end));
+        ranges
+    }
+
+    fn push(&mut self, loc: Loc, token_type: SemanticTokenType, modifiers: u32) {
+        if loc.begin < loc.end {
+            self.ranges.push(ClassifiedRange {
+                loc,
+                token_type,
+                modifiers,
+            })
+        }
+    }
+
+    fn source(&self, loc: &Loc) -> &'a [u8] {
+        self.input.substr_at(loc.begin, loc.end).unwrap_or(&[])
+    }
+
+    fn current_literal(&self) -> Option<&Literal> {
+        match self.stack.last() {
+            Some(Frame::Literal(literal)) => Some(literal),
+            _ => None,
+        }
+    }
+
+    fn begin_literal(&mut self, token: &Token, token_type: SemanticTokenType) {
+        let literal = Literal::new(token_type, self.source(&token.loc));
+        let begin_type = if literal.heredoc {
+            SemanticTokenType::HeredocId
+        } else {
+            token_type
+        };
+        self.push(token.loc, begin_type, 0);
+        self.stack.push(Frame::Literal(literal));
+    }
+
+    fn end_literal(&mut self, token: &Token, token_type: Option<SemanticTokenType>) {
+        let (literal_type, heredoc) = match self.current_literal() {
+            Some(literal) => (literal.token_type, literal.heredoc),
+            None => (SemanticTokenType::String, false),
+        };
+        let token_type = if heredoc {
+            SemanticTokenType::HeredocId
+        } else {
+            token_type.unwrap_or(literal_type)
+        };
+        self.push(token.loc, token_type, 0);
+        if let Some(Frame::Literal(_)) = self.stack.last() {
+            self.stack.pop();
+        }
+    }
+
+    fn on_token(&mut self, token: &Token) {
+        if std::mem::take(&mut self.symbol_pending) {
+            // `:foo`, `:+`, `:class`, `:@ivar`
+            self.push(token.loc, SemanticTokenType::Symbol, 0);
+            return;
+        }
+
+        match token.token_type {
+            Lexer::tSTRING_BEG | Lexer::tXSTRING_BEG | Lexer::tWORDS_BEG | Lexer::tQWORDS_BEG => {
+                self.begin_literal(token, SemanticTokenType::String)
+            }
+            Lexer::tSYMBOLS_BEG | Lexer::tQSYMBOLS_BEG => {
+                self.begin_literal(token, SemanticTokenType::Symbol)
+            }
+            Lexer::tREGEXP_BEG => self.begin_literal(token, SemanticTokenType::Regexp),
+            Lexer::tSYMBEG => {
+                if token.loc.size() == 1 {
+                    self.push(token.loc, SemanticTokenType::Symbol, 0);
+                    self.symbol_pending = true;
+                } else {
+                    // `:"dsym"`, `%s(sym)`
+                    self.begin_literal(token, SemanticTokenType::Symbol)
+                }
+            }
+            Lexer::tSTRING_CONTENT => self.on_string_content(token),
+            Lexer::tSTRING_END | Lexer::tREGEXP_END => self.end_literal(token, None),
+            Lexer::tLABEL_END => self.end_literal(token, Some(SemanticTokenType::Symbol)),
+            Lexer::tSTRING_DBEG => {
+                self.push(token.loc, SemanticTokenType::InterpolationDelimiter, 0);
+                self.stack.push(Frame::Interpolation);
+            }
+            Lexer::tSTRING_DEND => {
+                self.push(token.loc, SemanticTokenType::InterpolationDelimiter, 0);
+                if let Some(Frame::Interpolation) = self.stack.last() {
+                    self.stack.pop();
+                }
+            }
+            Lexer::tSTRING_DVAR => {
+                self.push(token.loc, SemanticTokenType::InterpolationDelimiter, 0)
+            }
+            Lexer::tCHAR => self.push(token.loc, SemanticTokenType::String, 0),
+
+            Lexer::tIDENTIFIER | Lexer::tFID => {
+                let (token_type, modifiers) = self
+                    .ast_info
+                    .get(&token.loc)
+                    .unwrap_or((SemanticTokenType::Method, 0));
+                self.push(token.loc, token_type, modifiers)
+            }
+            Lexer::tCONSTANT => {
+                let (token_type, modifiers) = match self.ast_info.get(&token.loc) {
+                    Some((SemanticTokenType::Method, modifiers)) => {
+                        (SemanticTokenType::Method, modifiers)
+                    }
+                    _ => (SemanticTokenType::Constant, 0),
+                };
+                self.push(token.loc, token_type, modifiers)
+            }
+            Lexer::tLABEL => {
+                let (token_type, modifiers) = match self.ast_info.get(&token.loc) {
+                    Some((SemanticTokenType::Parameter, modifiers)) => {
+                        (SemanticTokenType::Parameter, modifiers)
+                    }
+                    _ => (SemanticTokenType::Symbol, 0),
+                };
+                self.push(token.loc, token_type, modifiers)
+            }
+            Lexer::tIVAR => self.push(token.loc, SemanticTokenType::InstanceVariable, 0),
+            Lexer::tCVAR => self.push(token.loc, SemanticTokenType::ClassVariable, 0),
+            Lexer::tGVAR | Lexer::tNTH_REF | Lexer::tBACK_REF => {
+                self.push(token.loc, SemanticTokenType::GlobalVariable, 0)
+            }
+            Lexer::tINTEGER | Lexer::tFLOAT | Lexer::tRATIONAL | Lexer::tIMAGINARY => {
+                self.push(token.loc, SemanticTokenType::Number, 0)
+            }
+
+            _ if token.kind().map_or(false, |kind| kind.is_operator()) => {
+                self.push(token.loc, SemanticTokenType::Operator, 0)
+            }
+
+            _ if token.kind().map_or(false, |kind| kind.is_keyword()) => {
+                // keywords used as method names, like `def class`
+                let (token_type, modifiers) = match self.ast_info.get(&token.loc) {
+                    Some((SemanticTokenType::Method, modifiers)) => {
+                        (SemanticTokenType::Method, modifiers)
+                    }
+                    _ => (SemanticTokenType::Keyword, 0),
+                };
+                self.push(token.loc, token_type, modifiers)
+            }
+
+            _ => {
+                // punctuation, newlines and spaces are not highlighted
+            }
+        }
+    }
+
+    fn on_string_content(&mut self, token: &Token) {
+        let (token_type, interpolates, term) = match self.current_literal() {
+            Some(literal) => (literal.token_type, literal.interpolates, literal.term),
+            None => (SemanticTokenType::String, true, b'"'),
+        };
+
+        let source = self.source(&token.loc);
+        let mut start = 0;
+        let mut idx = 0;
+
+        while idx < source.len() {
+            if source[idx] != b'\\' {
+                idx += 1;
+                continue;
+            }
+
+            let len = if interpolates {
+                escape_len(&source[idx..])
+            } else if matches!(source.get(idx + 1), Some(c) if *c == b'\\' || *c == term) {
+                2
+            } else {
+                idx += 1;
+                continue;
+            };
+
+            let base = token.loc.begin;
+            self.push(
+                Loc {
+                    begin: base + start,
+                    end: base + idx,
+                },
+                token_type,
+                0,
+            );
+            self.push(
+                Loc {
+                    begin: base + idx,
+                    end: base + idx + len,
+                },
+                SemanticTokenType::StringEscape,
+                0,
+            );
+            idx += len;
+            start = idx;
+        }
+
+        self.push(token.loc.with_begin(token.loc.begin + start), token_type, 0);
+    }
+}
+
+/// Returns length of the escape sequence at the beginning of `source`
+/// (that starts with a backslash)
+fn escape_len(source: &[u8]) -> usize {
+    fn count_while(source: &[u8], max: usize, f: impl Fn(u8) -> bool) -> usize {
+        source.iter().take(max).take_while(|c| f(**c)).count()
+    }
+
+    let rest = &source[1..];
+    let len = match rest {
+        [b'u', b'{', ..] => match rest.iter().position(|c| *c == b'}') {
+            Some(pos) => pos + 1,
+            None => rest.len(),
+        },
+        [b'u', ..] => 1 + count_while(&rest[1..], 4, |c| c.is_ascii_hexdigit()),
+        [b'x', ..] => 1 + count_while(&rest[1..], 2, |c| c.is_ascii_hexdigit()),
+        [b'0'..=b'7', ..] => count_while(rest, 3, |c| (b'0'..=b'7').contains(&c)),
+        [b'c', ..] => 1 + escape_or_char_len(&rest[1..]),
+        [b'C' | b'M', b'-', ..] => 2 + escape_or_char_len(&rest[2..]),
+        [c, ..] => utf8_char_len(*c),
+        [] => 0,
+    };
+    (1 + len).min(source.len())
+}
+
+fn escape_or_char_len(source: &[u8]) -> usize {
+    match source.first() {
+        Some(b'\\') => escape_len(source),
+        Some(c) => utf8_char_len(*c),
+        None => 0,
+    }
+}
+
+fn utf8_char_len(first_byte: u8) -> usize {
+    match first_byte {
+        0xF0..=0xFF => 4,
+        0xE0..=0xEF => 3,
+        0xC0..=0xDF => 2,
+        _ => 1,
+    }
+}
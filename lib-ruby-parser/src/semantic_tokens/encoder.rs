@@ -0,0 +1,102 @@
+use crate::semantic_tokens::ClassifiedRange;
+use crate::source::DecodedInput;
+
+/// Semantic token in LSP delta encoding
+///
+/// Lines and columns are relative to the previous token,
+/// columns and lengths are measured in UTF-16 code units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(C)]
+pub struct SemanticToken {
+    /// Line relative to the line of the previous token
+    pub delta_line: u32,
+
+    /// Column relative to the column of the previous token
+    /// if it's on the same line, otherwise an absolute column
+    pub delta_start: u32,
+
+    /// Length of the token
+    pub length: u32,
+
+    /// Type of the token, index in `SemanticTokenType::LEGEND`
+    pub token_type: u32,
+
+    /// Bitset of `SemanticTokenModifiers`
+    pub token_modifiers_bitset: u32,
+}
+
+impl SemanticToken {
+    /// Converts a list of tokens into a flat list of integers,
+    /// i.e. into a `data` field of the LSP `SemanticTokens`
+    pub fn into_data(tokens: &[SemanticToken]) -> Vec<u32> {
+        tokens
+            .iter()
+            .flat_map(|token| {
+                [
+                    token.delta_line,
+                    token.delta_start,
+                    token.length,
+                    token.token_type,
+                    token.token_modifiers_bitset,
+                ]
+            })
+            .collect()
+    }
+}
+
+fn utf16_len(bytes: &[u8]) -> u32 {
+    String::from_utf8_lossy(bytes).encode_utf16().count() as u32
+}
+
+/// Converts sorted non-overlapping ranges into LSP semantic tokens.
+///
+/// LSP tokens can't span multiple lines,
+/// so multi-line ranges are split into one token per line.
+pub fn encode(ranges: &[ClassifiedRange], input: &DecodedInput) -> Vec<SemanticToken> {
+    let mut tokens = vec![];
+    let mut prev_line = 0;
+    let mut prev_start = 0;
+    let mut line_idx = 0;
+
+    for range in ranges {
+        while line_idx < input.lines.len() && input.lines[line_idx].end <= range.loc.begin {
+            line_idx += 1;
+        }
+
+        for (lineno, line) in input.lines.iter().enumerate().skip(line_idx) {
+            if line.start >= range.loc.end {
+                break;
+            }
+
+            let begin = range.loc.begin.max(line.start);
+            let end = range.loc.end.min(line.line_end());
+            if begin >= end {
+                continue;
+            }
+
+            let start = utf16_len(&input.bytes[line.start..begin]);
+            let length = utf16_len(&input.bytes[begin..end]);
+
+            let lineno = lineno as u32;
+            let delta_line = lineno - prev_line;
+            let delta_start = if delta_line == 0 {
+                start - prev_start
+            } else {
+                start
+            };
+
+            tokens.push(SemanticToken {
+                delta_line,
+                delta_start,
+                length,
+                token_type: range.token_type as u32,
+                token_modifiers_bitset: range.modifiers,
+            });
+
+            prev_line = lineno;
+            prev_start = start;
+        }
+    }
+
+    tokens
+}
@@ -0,0 +1,76 @@
+mod ast_info;
+mod classifier;
+mod encoder;
+mod token_type;
+
+pub use encoder::{encode, SemanticToken};
+pub use token_type::{SemanticTokenModifiers, SemanticTokenType};
+
+use crate::{Loc, ParserResult};
+
+/// Classified range of the source code
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[repr(C)]
+pub struct ClassifiedRange {
+    /// Location of the range, can span multiple lines
+    pub loc: Loc,
+
+    /// Type of the range
+    pub token_type: SemanticTokenType,
+
+    /// Bitset of `SemanticTokenModifiers`
+    pub modifiers: u32,
+}
+
+/// Classifies source ranges of a given `ParserResult`
+/// for syntax highlighting.
+///
+/// Uses both `tokens` (so they must be recorded, see `ParserOptions::record_tokens`)
+/// and AST (to tell local variables from method calls, parameters, etc).
+///
+/// Returned ranges are sorted and don't overlap.
+/// Punctuation and whitespaces are not included.
+///
+/// # Example
+/// ```
+/// use lib_ruby_parser::semantic_tokens::{classify, SemanticTokenType};
+/// use lib_ruby_parser::{Parser, ParserOptions};
+///
+/// let result = Parser::new(b"foo = 1; foo; bar".to_vec(), ParserOptions::default()).do_parse();
+/// let types = classify(&result)
+///     .into_iter()
+///     .map(|range| range.token_type)
+///     .collect::<Vec<_>>();
+///
+/// assert_eq!(
+///     types,
+///     vec![
+///         SemanticTokenType::LocalVariable,
+///         SemanticTokenType::Operator,
+///         SemanticTokenType::Number,
+///         SemanticTokenType::LocalVariable,
+///         SemanticTokenType::Method,
+///     ]
+/// );
+/// ```
+pub fn classify(result: &ParserResult) -> Vec<ClassifiedRange> {
+    let mut ranges = classifier::Classifier::run(result);
+    let mut last_end = 0;
+    ranges.retain(|range| {
+        if range.loc.begin < last_end {
+            return false;
+        }
+        last_end = range.loc.end;
+        true
+    });
+    ranges
+}
+
+/// Classifies source ranges of a given `ParserResult` (see `classify`)
+/// and returns them in LSP semantic tokens delta encoding
+pub fn semantic_tokens(result: &ParserResult) -> Vec<SemanticToken> {
+    encode(&classify(result), &result.input)
+}
+
+#[cfg(test)]
+mod tests;
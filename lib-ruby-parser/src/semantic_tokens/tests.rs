@@ -0,0 +1,169 @@
+use crate::semantic_tokens::{
+    classify, semantic_tokens, SemanticToken, SemanticTokenModifiers, SemanticTokenType,
+};
+use crate::{Parser, ParserOptions};
+
+fn highlight(src: &str) -> Vec<(String, SemanticTokenType, u32)> {
+    let result = Parser::new(src, ParserOptions::default()).do_parse();
+    classify(&result)
+        .into_iter()
+        .map(|range| {
+            (
+                range.loc.source(&result.input).unwrap(),
+                range.token_type,
+                range.modifiers,
+            )
+        })
+        .collect()
+}
+
+fn types(src: &str) -> Vec<(String, SemanticTokenType)> {
+    highlight(src)
+        .into_iter()
+        .map(|(source, token_type, _)| (source, token_type))
+        .collect()
+}
+
+macro_rules! assert_types {
+    ($src:expr, [$(($source:expr, $token_type:ident)),* $(,)?]) => {
+        assert_eq!(
+            types($src),
+            vec![$(($source.to_string(), SemanticTokenType::$token_type)),*]
+        )
+    };
+}
+
+#[test]
+fn test_lvar_vs_send() {
+    assert_eq!(
+        highlight("def m(a); a; b; end"),
+        vec![
+            ("def".to_string(), SemanticTokenType::Keyword, 0),
+            (
+                "m".to_string(),
+                SemanticTokenType::Method,
+                SemanticTokenModifiers::DECLARATION
+            ),
+            (
+                "a".to_string(),
+                SemanticTokenType::Parameter,
+                SemanticTokenModifiers::DECLARATION
+            ),
+            ("a".to_string(), SemanticTokenType::LocalVariable, 0),
+            ("b".to_string(), SemanticTokenType::Method, 0),
+            ("end".to_string(), SemanticTokenType::Keyword, 0),
+        ]
+    );
+}
+
+#[test]
+fn test_variables() {
+    assert_types!(
+        "@a; @@b; $c; $1; C::D",
+        [
+            ("@a", InstanceVariable),
+            ("@@b", ClassVariable),
+            ("$c", GlobalVariable),
+            ("$1", GlobalVariable),
+            ("C", Constant),
+            ("::", Operator),
+            ("D", Constant),
+        ]
+    );
+}
+
+#[test]
+fn test_string_with_escapes_and_interpolation() {
+    assert_types!(
+        r#""a\n#{1}b""#,
+        [
+            ("\"", String),
+            ("a", String),
+            ("\\n", StringEscape),
+            ("#{", InterpolationDelimiter),
+            ("1", Number),
+            ("}", InterpolationDelimiter),
+            ("b", String),
+            ("\"", String),
+        ]
+    );
+}
+
+#[test]
+fn test_single_quoted_string() {
+    assert_types!(
+        r"'a\nb\''",
+        [
+            ("'", String),
+            ("a\\nb", String),
+            ("\\'", StringEscape),
+            ("'", String),
+        ]
+    );
+}
+
+#[test]
+fn test_symbols_and_regexps() {
+    assert_types!(
+        "[:foo, :\"bar\", baz: /x\\d/]",
+        [
+            (":", Symbol),
+            ("foo", Symbol),
+            (":\"", Symbol),
+            ("bar", Symbol),
+            ("\"", Symbol),
+            ("baz:", Symbol),
+            ("/", Regexp),
+            ("x", Regexp),
+            ("\\d", StringEscape),
+            ("/", Regexp),
+        ]
+    );
+}
+
+#[test]
+fn test_heredoc_and_comments() {
+    assert_types!(
+        "foo(<<-HERE) # comment\ntext\nHERE\n",
+        [
+            ("foo", Method),
+            ("<<-HERE", HeredocId),
+            ("# comment", Comment),
+            ("text\n", String),
+            ("HERE", HeredocId),
+        ]
+    );
+}
+
+#[rustfmt::skip]
+#[test]
+fn test_delta_encoding() {
+    let result = Parser::new("a = 1\n  b\n=begin\nx\n=end\n", ParserOptions::default()).do_parse();
+
+    assert_eq!(
+        SemanticToken::into_data(&semantic_tokens(&result)),
+        vec![
+            0, 0, 1, SemanticTokenType::LocalVariable as u32, SemanticTokenModifiers::DECLARATION,
+            0, 2, 1, SemanticTokenType::Operator as u32, 0,
+            0, 2, 1, SemanticTokenType::Number as u32, 0,
+            1, 2, 1, SemanticTokenType::Method as u32, 0,
+            1, 0, 6, SemanticTokenType::Comment as u32, 0,
+            1, 0, 1, SemanticTokenType::Comment as u32, 0,
+            1, 0, 4, SemanticTokenType::Comment as u32, 0,
+        ]
+    );
+}
+
+#[test]
+fn test_utf16_columns() {
+    let result = Parser::new("\"ё\"; a", ParserOptions::default()).do_parse();
+    let tokens = semantic_tokens(&result);
+
+    assert_eq!(
+        tokens
+            .iter()
+            .map(|token| (token.delta_start, token.length))
+            .collect::<Vec<_>>(),
+        vec![(0, 1), (1, 1), (1, 1), (2, 1)]
+    );
+}
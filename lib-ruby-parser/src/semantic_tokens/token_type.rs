@@ -0,0 +1,92 @@
+/// Enum of all semantic token types,
+/// numeric value of each variant is its index in `SemanticTokenType::LEGEND`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub enum SemanticTokenType {
+    /// `def`, `if`, `self`, `nil`, ...
+    Keyword,
+    /// Name of the method in a method call or in a method definition
+    Method,
+    /// Local variable
+    LocalVariable,
+    /// Method or block parameter
+    Parameter,
+    /// `@ivar`
+    InstanceVariable,
+    /// `$gvar`, `$1`, `$&`
+    GlobalVariable,
+    /// `@@cvar`
+    ClassVariable,
+    /// `Const`
+    Constant,
+    /// `:sym`, `:"dsym"`, `%i[sym]`, `label:`
+    Symbol,
+    /// Content and quotes of a string literal
+    String,
+    /// Escape sequence inside of a string, symbol or regexp literal
+    StringEscape,
+    /// `#{`, `}` and `#` around interpolated code
+    InterpolationDelimiter,
+    /// Content and delimiters of a regexp literal
+    Regexp,
+    /// Integer, float, rational and imaginary numbers
+    Number,
+    /// `# comment` or `=begin`/`=end` block
+    Comment,
+    /// `<<~HEREDOC` and a heredoc terminator
+    HeredocId,
+    /// `+`, `&&`, `=`, `::`, ...
+    Operator,
+}
+
+impl SemanticTokenType {
+    /// Names of all token types in the order of their numeric values,
+    /// can be used as `tokenTypes` of the LSP `SemanticTokensLegend`
+    pub const LEGEND: &'static [&'static str] = &[
+        "keyword",
+        "method",
+        "variable",
+        "parameter",
+        "instanceVariable",
+        "globalVariable",
+        "classVariable",
+        "constant",
+        "symbol",
+        "string",
+        "escapeSequence",
+        "interpolationDelimiter",
+        "regexp",
+        "number",
+        "comment",
+        "heredocId",
+        "operator",
+    ];
+
+    /// Returns a name of the token type from `SemanticTokenType::LEGEND`
+    pub fn name(&self) -> &'static str {
+        Self::LEGEND[*self as usize]
+    }
+}
+
+/// Bitset of semantic token modifiers
+#[derive(Debug)]
+pub struct SemanticTokenModifiers;
+
+impl SemanticTokenModifiers {
+    /// Set for names of defined methods, parameters and assigned local variables
+    pub const DECLARATION: u32 = 1 << 0;
+
+    /// Names of all modifiers in the order of their bits,
+    /// can be used as `tokenModifiers` of the LSP `SemanticTokensLegend`
+    pub const LEGEND: &'static [&'static str] = &["declaration"];
+}
+
+#[test]
+fn test_legend() {
+    assert_eq!(SemanticTokenType::Keyword.name(), "keyword");
+    assert_eq!(SemanticTokenType::Operator.name(), "operator");
+    assert_eq!(
+        SemanticTokenType::LEGEND.len(),
+        SemanticTokenType::Operator as usize + 1
+    );
+}
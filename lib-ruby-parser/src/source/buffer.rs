@@ -37,12 +37,104 @@ pub(crate) struct Buffer {
     // pub(crate) ruby_sourcefile_string: Vec<char>,
 }
 
-macro_rules! println_if_debug_buffer {
-    ($fmt_string:expr, $( $arg:expr ),*) => {
-        if cfg!(feature = "debug-buffer") {
-            println!($fmt_string, $( $arg ),*);
+/// Position of the `Buffer` (everything except the input itself)
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BufferState {
+    line_count: usize,
+    prevline: Option<usize>,
+    lastline: usize,
+    nextline: usize,
+    pbeg: usize,
+    pcur: usize,
+    pend: usize,
+    ptok: usize,
+
+    eofp: bool,
+    cr_seen: bool,
+
+    heredoc_end: usize,
+    heredoc_indent: i32,
+    heredoc_line_indent: i32,
+
+    tokidx: usize,
+    tokline: usize,
+
+    has_shebang: bool,
+    ruby_sourceline: usize,
+}
+
+impl BufferState {
+    /// Returns `true` if the buffer is not going to jump to another line
+    /// (after a heredoc or a look-ahead of the next line)
+    pub(crate) fn is_sequential(&self) -> bool {
+        self.nextline == 0 && self.heredoc_end == 0
+    }
+
+    /// Returns `true` if both states are equal after shifting `self`
+    /// by `byte_delta` bytes starting from `old_end`
+    pub(crate) fn is_shifted_eq(&self, other: &Self, old_end: usize, byte_delta: isize) -> bool {
+        let shift = |offset| shift_offset(offset, old_end, byte_delta);
+
+        shift(self.pbeg) == other.pbeg
+            && shift(self.pcur) == other.pcur
+            && shift(self.pend) == other.pend
+            && shift(self.ptok) == other.ptok
+            && self.eofp == other.eofp
+            && self.cr_seen == other.cr_seen
+            && self.heredoc_indent == other.heredoc_indent
+            && self.heredoc_line_indent == other.heredoc_line_indent
+            && self.is_sequential()
+            && other.is_sequential()
+    }
+
+    /// Moves byte offsets starting from `old_end` by `byte_delta`
+    /// and all line numbers by `line_delta`
+    pub(crate) fn shift(&mut self, old_end: usize, byte_delta: isize, line_delta: isize) {
+        let shift = |offset| shift_offset(offset, old_end, byte_delta);
+
+        self.pbeg = shift(self.pbeg);
+        self.pcur = shift(self.pcur);
+        self.pend = shift(self.pend);
+        self.ptok = shift(self.ptok);
+
+        self.line_count = shift_line(self.line_count, line_delta);
+        self.prevline = self.prevline.map(|line| shift_line(line, line_delta));
+        self.lastline = shift_line(self.lastline, line_delta);
+        if self.nextline != 0 {
+            self.nextline = shift_line(self.nextline, line_delta);
         }
-    };
+        if self.heredoc_end != 0 {
+            self.heredoc_end = shift_line(self.heredoc_end, line_delta);
+        }
+        self.tokline = shift_line(self.tokline, line_delta);
+        self.ruby_sourceline = shift_line(self.ruby_sourceline, line_delta);
+    }
+
+    pub(crate) fn line_count(&self) -> usize {
+        self.line_count
+    }
+
+    pub(crate) fn line_begin(&self) -> usize {
+        self.pbeg
+    }
+
+    pub(crate) fn is_eof(&self) -> bool {
+        self.eofp
+    }
+}
+
+/// Moves a given byte offset by `byte_delta` if it's after the edited range
+pub(crate) fn shift_offset(offset: usize, old_end: usize, byte_delta: isize) -> usize {
+    if offset >= old_end {
+        (offset as isize + byte_delta) as usize
+    } else {
+        offset
+    }
+}
+
+/// Moves a given line number by `line_delta`
+pub(crate) fn shift_line(line: usize, line_delta: isize) -> usize {
+    (line as isize + line_delta) as usize
 }
 
 impl Buffer {
@@ -90,10 +182,64 @@ impl Buffer {
         self.pushback(c)
     }
 
+    pub(crate) fn state(&self) -> BufferState {
+        BufferState {
+            line_count: self.line_count,
+            prevline: self.prevline,
+            lastline: self.lastline,
+            nextline: self.nextline,
+            pbeg: self.pbeg,
+            pcur: self.pcur,
+            pend: self.pend,
+            ptok: self.ptok,
+            eofp: self.eofp,
+            cr_seen: self.cr_seen,
+            heredoc_end: self.heredoc_end,
+            heredoc_indent: self.heredoc_indent,
+            heredoc_line_indent: self.heredoc_line_indent,
+            tokidx: self.tokidx,
+            tokline: self.tokline,
+            has_shebang: self.has_shebang,
+            ruby_sourceline: self.ruby_sourceline,
+        }
+    }
+
+    pub(crate) fn restore_state(&mut self, state: &BufferState) {
+        self.line_count = state.line_count;
+        self.prevline = state.prevline;
+        self.lastline = state.lastline;
+        self.nextline = state.nextline;
+        self.pbeg = state.pbeg;
+        self.pcur = state.pcur;
+        self.pend = state.pend;
+        self.ptok = state.ptok;
+        self.eofp = state.eofp;
+        self.cr_seen = state.cr_seen;
+        self.heredoc_end = state.heredoc_end;
+        self.heredoc_indent = state.heredoc_indent;
+        self.heredoc_line_indent = state.heredoc_line_indent;
+        self.tokidx = state.tokidx;
+        self.tokline = state.tokline;
+        self.has_shebang = state.has_shebang;
+        self.ruby_sourceline = state.ruby_sourceline;
+    }
+
+    pub(crate) fn is_sequential(&self) -> bool {
+        self.nextline == 0 && self.heredoc_end == 0
+    }
+
+    /// Returns the end of the last line read by the buffer,
+    /// the state of the buffer doesn't depend on anything after it
+    pub(crate) fn consumed_end(&self) -> usize {
+        match self.line_count {
+            0 => 0,
+            line_count => self.input.line_at(line_count - 1).end,
+        }
+    }
+
     pub(crate) fn nextc(&mut self) -> MaybeByte {
         if self.pcur == self.pend || self.eofp || self.nextline != 0 {
             let n = self.nextline();
-            println_if_debug_buffer!("nextline = {:?}", n);
             if n.is_err() {
                 return MaybeByte::EndOfInput;
             }
@@ -106,7 +252,6 @@ impl Buffer {
         if c == b'\r' {
             c = self.parser_cr(c);
         }
-        println_if_debug_buffer!("nextc = {:?}", c);
         MaybeByte::new(c)
     }
 
@@ -187,7 +332,6 @@ impl Buffer {
     pub(crate) fn getline(&mut self) -> Result<usize, ()> {
         if self.line_count < self.input.lines_count() {
             self.line_count += 1;
-            println_if_debug_buffer!("line_count = {}", self.line_count);
             Ok(self.line_count - 1)
         } else {
             Err(())
@@ -199,7 +343,6 @@ impl Buffer {
     }
 
     pub(crate) fn set_ptok(&mut self, ptok: usize) {
-        println_if_debug_buffer!("set_ptok({})", ptok);
         self.ptok = ptok;
     }
 
@@ -332,7 +475,7 @@ pub(crate) trait Pushback<T> {
 }
 
 impl Pushback<u8> for Buffer {
-    fn pushback(&mut self, c: u8) {
+    fn pushback(&mut self, _c: u8) {
         self.pcur -= 1;
         if self.pcur > self.pbeg
             && self.byte_at(self.pcur) == b'\n'
@@ -340,7 +483,6 @@ impl Pushback<u8> for Buffer {
         {
             self.pcur -= 1;
         }
-        println_if_debug_buffer!("pushback({:?}) pcur = {}", c, self.pcur);
     }
 }
 
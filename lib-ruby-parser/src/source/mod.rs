@@ -4,7 +4,10 @@ mod decoded_input;
 pub(crate) mod decoder;
 mod input;
 mod magic_comment;
+mod shared_lines;
 mod source_line;
+mod suppression_comment;
+mod trivia;
 
 /// Module to perform token rewriting
 pub mod token_rewriter;
@@ -15,4 +18,9 @@ pub(crate) use decoder::decode_input;
 pub use decoder::{Decoder, DecoderResult, InputError};
 pub use input::Input;
 pub use magic_comment::{MagicComment, MagicCommentKind};
+pub(crate) use shared_lines::SharedLines;
+pub(crate) use source_line::line_of;
 pub use source_line::SourceLine;
+pub use suppression_comment::{SuppressionComment, SuppressionCommentKind};
+pub(crate) use trivia::{insert_trivia_tokens, scan_trivia};
+pub use trivia::{Trivia, TriviaKind};
@@ -0,0 +1,29 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::source::{line_of, SourceLine};
+
+/// Lines of the decoded input, shared between the lexer
+/// (that updates them when a magic comment changes the encoding)
+/// and the builder (that needs line numbers for its warnings)
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SharedLines {
+    lines: Rc<RefCell<Vec<SourceLine>>>,
+}
+
+impl SharedLines {
+    pub(crate) fn new(lines: &[SourceLine]) -> Self {
+        Self {
+            lines: Rc::new(RefCell::new(lines.to_vec())),
+        }
+    }
+
+    pub(crate) fn update(&self, lines: &[SourceLine]) {
+        *self.lines.borrow_mut() = lines.to_vec();
+    }
+
+    /// Returns 1-based number of the line that contains given `pos`
+    pub(crate) fn line_number(&self, pos: usize) -> usize {
+        line_of(&self.lines.borrow(), pos) + 1
+    }
+}
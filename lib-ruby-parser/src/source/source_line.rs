@@ -32,3 +32,10 @@ impl SourceLine {
         result
     }
 }
+
+/// Returns index of the line (0-based) that contains given `pos`
+pub(crate) fn line_of(lines: &[SourceLine], pos: usize) -> usize {
+    lines
+        .partition_point(|line| line.start <= pos)
+        .saturating_sub(1)
+}
@@ -0,0 +1,38 @@
+use crate::Loc;
+
+/// An enum of all suppression comment kinds
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SuppressionCommentKind {
+    /// `# lib-ruby-parser:disable Name` comment on its own line,
+    /// silences warnings until a matching `enable` comment (or until EOF)
+    Disable,
+
+    /// `# lib-ruby-parser:enable Name` comment,
+    /// closes a previously opened `disable` range
+    Enable,
+
+    /// `code # lib-ruby-parser:disable Name` comment
+    /// at the end of a line with code, silences warnings only on this line
+    DisableLine,
+}
+
+/// Representation of a suppression comment,
+/// one per each name listed in the comment
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuppressionComment {
+    /// Kind of a suppression comment
+    pub kind: SuppressionCommentKind,
+
+    /// Name of the diagnostic message, i.e. the name of the `DiagnosticMessage` variant
+    pub name: String,
+
+    /// Location of the name:
+    ///
+    /// ```text
+    /// # lib-ruby-parser:disable AmbiguousFirstArgument
+    ///                           ~~~~~~~~~~~~~~~~~~~~~~
+    /// ```
+    pub name_l: Loc,
+}
@@ -1,4 +1,4 @@
-use crate::Token;
+use crate::{ContextFlags, LexState, Token};
 
 /// Enum of what token rewriter should do with a token.
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -9,14 +9,22 @@ pub enum RewriteAction {
 
     /// Means "keep the token", i.e. return it to a parser
     Keep,
+
+    /// Means "replace the token with a given sequence of tokens",
+    /// i.e. return them to a parser one by one.
+    ///
+    /// Inserted tokens are not passed to the rewriter again,
+    /// an empty list is equivalent to `Drop`.
+    Replace(Vec<Token>),
 }
 
 /// Enum of what token rewriter should do with the state of the lexer
 #[derive(Debug, PartialEq, Eq, Clone)]
 #[repr(C)]
 pub enum LexStateAction {
-    /// Means "set the state to X"
-    Set(i32),
+    /// Means "set the state to X",
+    /// e.g. `LexStateAction::Set(LexState::EXPR_BEG | LexState::EXPR_LABEL)`
+    Set(LexState),
 
     /// Means "keep the state unchanged"
     Keep,
@@ -29,30 +37,63 @@ pub struct TokenRewriterResult {
     /// Rewritten token. Can be input token if no rewriting expected
     pub rewritten_token: Box<Token>,
 
-    /// Action to be applied on a token (keep or drop)
+    /// Action to be applied on a token:
+    /// keep `rewritten_token`, drop it or replace it with a sequence of tokens
+    /// (`rewritten_token` is ignored in this case).
+    ///
+    /// Tokens injected by `RewriteAction::Replace` are returned to a parser as is,
+    /// they are not passed back through the rewriter.
     pub token_action: RewriteAction,
 
     /// Action to be applied on lexer's state (keep as is or change)
     pub lex_state_action: LexStateAction,
 }
 
+/// Read-only state of the lexer and the parser
+/// at the moment when a token is passed to the rewriter
+#[derive(Debug, Clone, Copy)]
+pub struct TokenRewriterContext<'a> {
+    /// Source code
+    pub input: &'a [u8],
+
+    /// Current state of the lexer (i.e. after reading the token)
+    pub lex_state: LexState,
+
+    /// Current parser context flags (`in_def`, `in_class`, `in_block`, ...)
+    pub context: ContextFlags,
+}
+
 /// Token rewriter function
 pub type TokenRewriterFn = dyn Fn(Box<Token>, &[u8]) -> TokenRewriterResult;
 
+/// Token rewriter function that has access to the state of the lexer and the parser
+pub type TokenRewriterWithContextFn =
+    dyn Fn(Box<Token>, &TokenRewriterContext) -> TokenRewriterResult;
+
 /// Token rewriter struct, can be used to rewrite tokens on the fly
 pub struct TokenRewriter {
-    f: Box<TokenRewriterFn>,
+    f: Box<TokenRewriterWithContextFn>,
 }
 
 impl TokenRewriter {
     /// Constructs a rewriter based on a given function
     pub fn new(f: Box<TokenRewriterFn>) -> Self {
+        Self::with_context(Box::new(move |token, context| f(token, context.input)))
+    }
+
+    /// Constructs a rewriter based on a given function
+    /// that also receives the state of the lexer and the parser
+    pub fn with_context(f: Box<TokenRewriterWithContextFn>) -> Self {
         Self { f }
     }
 
-    pub(crate) fn call(&self, token: Box<Token>, input: &[u8]) -> TokenRewriterResult {
+    pub(crate) fn call(
+        &self,
+        token: Box<Token>,
+        context: &TokenRewriterContext,
+    ) -> TokenRewriterResult {
         let f = &*self.f;
-        f(token, input)
+        f(token, context)
     }
 }
 
@@ -0,0 +1,234 @@
+use crate::source::{Comment, CommentType, DecodedInput};
+use crate::{Bytes, LexState, Lexer, Loc, Token};
+
+/// An enum of all trivia kinds,
+/// i.e. kinds of source ranges that are ignored by the parser
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriviaKind {
+    /// A run of spaces, tabs and other non-newline whitespace characters
+    Whitespace,
+
+    /// A newline that is not a `tNL` token
+    Newline,
+
+    /// `# comment`
+    Comment,
+
+    /// `=begin`/`=end` block
+    EmbeddedDocument,
+
+    /// Backslash followed by a newline
+    LineContinuation,
+
+    /// `__END__` marker and all data after it
+    EndOfData,
+
+    /// Anything else that is skipped by the lexer
+    Unknown,
+}
+
+/// A piece of source code that is ignored by the parser
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Trivia {
+    /// Kind of the trivia
+    pub kind: TriviaKind,
+
+    /// Location of the trivia
+    pub loc: Loc,
+}
+
+impl TriviaKind {
+    /// Returns a `token_type` of the synthetic trivia token
+    pub fn token_type(&self) -> i32 {
+        match self {
+            Self::Whitespace => Lexer::tWHITESPACE,
+            Self::Newline => Lexer::tIGNORED_NL,
+            Self::Comment => Lexer::tCOMMENT,
+            Self::EmbeddedDocument => Lexer::tEMBDOC,
+            Self::LineContinuation => Lexer::tLINE_CONTINUATION,
+            Self::EndOfData => Lexer::tEND_OF_DATA,
+            Self::Unknown => Lexer::tUNKNOWN_TRIVIA,
+        }
+    }
+
+    /// Returns a trivia kind by a `token_type` of the synthetic trivia token
+    pub fn from_token_type(token_type: i32) -> Option<Self> {
+        let kind = match token_type {
+            Lexer::tWHITESPACE => Self::Whitespace,
+            Lexer::tIGNORED_NL => Self::Newline,
+            Lexer::tCOMMENT => Self::Comment,
+            Lexer::tEMBDOC => Self::EmbeddedDocument,
+            Lexer::tLINE_CONTINUATION => Self::LineContinuation,
+            Lexer::tEND_OF_DATA => Self::EndOfData,
+            Lexer::tUNKNOWN_TRIVIA => Self::Unknown,
+            _ => return None,
+        };
+        Some(kind)
+    }
+
+    pub(crate) fn token_name(&self) -> &'static str {
+        match self {
+            Self::Whitespace => "tWHITESPACE",
+            Self::Newline => "tIGNORED_NL",
+            Self::Comment => "tCOMMENT",
+            Self::EmbeddedDocument => "tEMBDOC",
+            Self::LineContinuation => "tLINE_CONTINUATION",
+            Self::EndOfData => "tEND_OF_DATA",
+            Self::Unknown => "tUNKNOWN_TRIVIA",
+        }
+    }
+}
+
+const END_OF_DATA_MARKER: &[u8] = b"__END__";
+
+/// Splits a given range of source code (a gap between two tokens)
+/// into trivia pieces.
+///
+/// `comments` must be sorted by location.
+pub(crate) fn scan_trivia(
+    input: &DecodedInput,
+    comments: &[Comment],
+    begin: usize,
+    end: usize,
+) -> Vec<Trivia> {
+    let bytes = input.as_shared_bytes();
+    let mut result = vec![];
+    let mut comment_idx = comments.partition_point(|comment| comment.location.begin < begin);
+    let mut pos = begin;
+
+    while pos < end {
+        let (kind, len) = match comments.get(comment_idx) {
+            Some(comment) if comment.location.begin == pos => {
+                comment_idx += 1;
+                let kind = match comment.kind {
+                    CommentType::Document => TriviaKind::EmbeddedDocument,
+                    _ => TriviaKind::Comment,
+                };
+                (kind, comment.location.size().max(1))
+            }
+            _ => scan_one(bytes, pos, end),
+        };
+
+        let piece_end = (pos + len).min(end);
+        result.push(Trivia {
+            kind,
+            loc: Loc {
+                begin: pos,
+                end: piece_end,
+            },
+        });
+        pos = piece_end;
+    }
+
+    result
+}
+
+/// Inserts synthetic trivia tokens into gaps between `tokens`,
+/// so concatenation of all token ranges covers the whole input.
+///
+/// Returned tokens are sorted by location,
+/// `END_OF_INPUT` token (if any) is kept at the end.
+pub(crate) fn insert_trivia_tokens(
+    tokens: Vec<Token>,
+    input: &DecodedInput,
+    comments: &[Comment],
+) -> Vec<Token> {
+    let mut comments = comments.to_vec();
+    comments.sort_by_key(|comment| comment.location.begin);
+
+    let (mut tokens, eof): (Vec<Token>, Vec<Token>) = tokens
+        .into_iter()
+        .partition(|token| token.token_type != Lexer::END_OF_INPUT);
+    tokens.sort_by_key(|token| (token.loc.begin, token.loc.end));
+
+    let mut result = Vec::with_capacity(tokens.len() * 2);
+    let mut pos = 0;
+    let push_trivia = |result: &mut Vec<Token>, begin: usize, end: usize, state: LexState| {
+        for trivia in scan_trivia(input, &comments, begin, end) {
+            result.push(Token {
+                token_type: trivia.kind.token_type(),
+                token_value: Bytes::new(input.as_shared_bytes()[trivia.loc.to_range()].to_vec()),
+                loc: trivia.loc,
+                lex_state_before: state,
+                lex_state_after: state,
+            })
+        }
+    };
+
+    for token in tokens {
+        if token.loc.begin > pos {
+            push_trivia(&mut result, pos, token.loc.begin, token.lex_state_before);
+        }
+        pos = pos.max(token.loc.end);
+        result.push(token);
+    }
+    let state = result
+        .last()
+        .map(|token| token.lex_state_after)
+        .unwrap_or_default();
+    push_trivia(&mut result, pos, input.len(), state);
+
+    result.extend(eof);
+    result
+}
+
+fn scan_one(bytes: &[u8], pos: usize, end: usize) -> (TriviaKind, usize) {
+    let rest = &bytes[pos..end];
+    let at_line_start = pos == 0 || bytes[pos - 1] == b'\n';
+
+    match rest {
+        [b'\n', ..] => (TriviaKind::Newline, 1),
+        [b'\\', b'\n', ..] => (TriviaKind::LineContinuation, 2),
+        [b'\\', b'\r', b'\n', ..] => (TriviaKind::LineContinuation, 3),
+        _ if at_line_start && is_end_of_data_marker(rest) => (TriviaKind::EndOfData, rest.len()),
+        [c, ..] if is_whitespace(*c) => {
+            let len = rest.iter().take_while(|c| is_whitespace(**c)).count();
+            (TriviaKind::Whitespace, len)
+        }
+        _ => {
+            let len = rest
+                .iter()
+                .take_while(|c| !is_whitespace(**c) && **c != b'\n' && **c != b'\\')
+                .count();
+            (TriviaKind::Unknown, len.max(1))
+        }
+    }
+}
+
+fn is_whitespace(c: u8) -> bool {
+    matches!(c, b' ' | b'\t' | b'\r' | 0x0b | 0x0c)
+}
+
+fn is_end_of_data_marker(rest: &[u8]) -> bool {
+    match rest.strip_prefix(END_OF_DATA_MARKER) {
+        Some(after) => matches!(after, [] | [b'\n', ..] | [b'\r', b'\n', ..]),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+fn scan(src: &str) -> Vec<(TriviaKind, String)> {
+    let mut input = DecodedInput::named("(trivia_test)");
+    input.update_bytes(src.as_bytes().to_vec());
+    let comments = vec![Comment::new(Loc { begin: 2, end: 5 }, &input)];
+    scan_trivia(&input, &comments, 0, src.len())
+        .into_iter()
+        .map(|trivia| (trivia.kind, trivia.loc.source(&input).unwrap()))
+        .collect()
+}
+
+#[test]
+fn test_scan_trivia() {
+    assert_eq!(
+        scan(" \t# c\n\\\n__END__\ndata"),
+        vec![
+            (TriviaKind::Whitespace, " \t".to_string()),
+            (TriviaKind::Comment, "# c".to_string()),
+            (TriviaKind::Newline, "\n".to_string()),
+            (TriviaKind::LineContinuation, "\\\n".to_string()),
+            (TriviaKind::EndOfData, "__END__\ndata".to_string()),
+        ]
+    );
+}
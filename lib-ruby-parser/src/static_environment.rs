@@ -2,6 +2,8 @@ use std::cell::RefCell;
 use std::collections::BTreeSet;
 use std::rc::Rc;
 
+use crate::Loc;
+
 /// Stack of local variables in nested scopes
 ///
 /// Each scope represents a Ruby scope:
@@ -25,6 +27,24 @@ use std::rc::Rc;
 pub struct StaticEnvironment {
     variables: Rc<RefCell<BTreeSet<String>>>,
     stack: Rc<RefCell<Vec<BTreeSet<String>>>>,
+    usage: Rc<RefCell<Vec<VariablesUsage>>>,
+}
+
+/// Variables declared in a single scope (without inherited ones)
+/// and assigned variables that are not read yet
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct VariablesUsage {
+    dynamic: bool,
+    declared: BTreeSet<String>,
+    unused: Vec<(String, Loc)>,
+}
+
+/// Deep copy of all scopes of the `StaticEnvironment`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct StaticEnvironmentSnapshot {
+    variables: BTreeSet<String>,
+    stack: Vec<BTreeSet<String>>,
+    usage: Vec<VariablesUsage>,
 }
 
 const FORWARD_ARGS: &str = "FORWARD_ARGS";
@@ -36,6 +56,8 @@ impl StaticEnvironment {
         Self {
             variables: Rc::new(RefCell::new(BTreeSet::new())),
             stack: Rc::new(RefCell::new(vec![])),
+            // top-level scope
+            usage: Rc::new(RefCell::new(vec![VariablesUsage::default()])),
         }
     }
 
@@ -49,6 +71,7 @@ impl StaticEnvironment {
     pub fn extend_static(&self) {
         let variables = std::mem::take(&mut *self.variables.borrow_mut());
         self.stack.borrow_mut().push(variables);
+        self.usage.borrow_mut().push(VariablesUsage::default());
     }
 
     /// Performs a puch, inherits previously declared variables in the new scope
@@ -58,6 +81,10 @@ impl StaticEnvironment {
         self.stack
             .borrow_mut()
             .push(self.variables.borrow().clone());
+        self.usage.borrow_mut().push(VariablesUsage {
+            dynamic: true,
+            ..Default::default()
+        });
     }
 
     /// Performs pop
@@ -67,11 +94,63 @@ impl StaticEnvironment {
             .borrow_mut()
             .pop()
             .expect("expected static_env to have at least one frame");
+        self.usage.borrow_mut().pop();
     }
 
     /// Declares a new variable in the current scope
     pub fn declare(&self, name: &str) {
         self.variables.borrow_mut().insert(name.to_string());
+        if let Some(usage) = self.usage.borrow_mut().last_mut() {
+            usage.declared.insert(name.to_string());
+        }
+    }
+
+    /// Declares a variable that is assigned at `loc` (unless it's already declared),
+    /// it's reported by `unused_variables` until it's read (see `mark_used`).
+    ///
+    /// Arguments must be declared using `declare`, they are never reported.
+    pub(crate) fn declare_assigned(&self, name: &str, loc: &Loc) {
+        if self.is_declared(name) {
+            return;
+        }
+        self.declare(name);
+        if let Some(usage) = self.usage.borrow_mut().last_mut() {
+            usage.unused.push((name.to_string(), *loc));
+        }
+    }
+
+    /// Marks a variable as read in the scope that declares it
+    pub(crate) fn mark_used(&self, name: &str) {
+        for usage in self.usage.borrow_mut().iter_mut().rev() {
+            if usage.declared.contains(name) {
+                usage.unused.retain(|(unused, _)| unused != name);
+                return;
+            }
+            if !usage.dynamic {
+                return;
+            }
+        }
+    }
+
+    /// Marks all variables that are visible in the current scope as read,
+    /// used when local variables can be accessed via `binding`
+    pub(crate) fn mark_all_used(&self) {
+        for usage in self.usage.borrow_mut().iter_mut().rev() {
+            usage.unused.clear();
+            if !usage.dynamic {
+                return;
+            }
+        }
+    }
+
+    /// Returns variables of the current scope that are assigned but never read
+    /// in the order of their first assignment
+    pub(crate) fn unused_variables(&self) -> Vec<(String, Loc)> {
+        self.usage
+            .borrow()
+            .last()
+            .map(|usage| usage.unused.clone())
+            .unwrap_or_default()
     }
 
     /// Returns `true` if variable with a given `name` is declared in the current scope
@@ -94,6 +173,20 @@ impl StaticEnvironment {
     pub(crate) fn is_anonymous_blockarg_declared(&self) -> bool {
         self.is_declared(ANONYMOUS_BLOCKARG)
     }
+
+    pub(crate) fn snapshot(&self) -> StaticEnvironmentSnapshot {
+        StaticEnvironmentSnapshot {
+            variables: self.variables.borrow().clone(),
+            stack: self.stack.borrow().clone(),
+            usage: self.usage.borrow().clone(),
+        }
+    }
+
+    pub(crate) fn restore(&self, snapshot: &StaticEnvironmentSnapshot) {
+        *self.variables.borrow_mut() = snapshot.variables.clone();
+        *self.stack.borrow_mut() = snapshot.stack.clone();
+        *self.usage.borrow_mut() = snapshot.usage.clone();
+    }
 }
 
 #[test]
@@ -129,6 +222,21 @@ fn test_extend_dynamic() {
     assert!(env.is_declared("bar"));
 }
 
+#[test]
+fn test_snapshot() {
+    let env = StaticEnvironment::new();
+
+    env.declare("foo");
+    let snapshot = env.snapshot();
+    env.extend_static();
+    env.declare("bar");
+
+    env.restore(&snapshot);
+    assert!(env.is_declared("foo"));
+    assert!(!env.is_declared("bar"));
+    assert!(env.is_empty());
+}
+
 #[test]
 fn test_unextend() {
     let env = StaticEnvironment::new();
@@ -141,3 +249,27 @@ fn test_unextend() {
     assert!(env.is_declared("foo"));
     assert!(!env.is_declared("bar"));
 }
+
+#[test]
+fn test_unused_variables() {
+    let env = StaticEnvironment::new();
+    let loc = |begin: usize| Loc {
+        begin,
+        end: begin + 1,
+    };
+
+    env.declare_assigned("a", &loc(0));
+    env.declare_assigned("b", &loc(1));
+    env.extend_dynamic();
+    // shadows `a`
+    env.declare("a");
+    env.declare_assigned("c", &loc(2));
+    env.mark_used("a");
+    env.mark_used("b");
+    assert_eq!(env.unused_variables(), vec![("c".to_string(), loc(2))]);
+    env.unextend();
+
+    assert_eq!(env.unused_variables(), vec![("a".to_string(), loc(0))]);
+    env.mark_all_used();
+    assert_eq!(env.unused_variables(), vec![]);
+}
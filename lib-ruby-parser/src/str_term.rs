@@ -1,3 +1,5 @@
+use crate::source::buffer::{shift_line, shift_offset};
+
 pub(crate) mod str_types {
     pub(crate) const STR_FUNC_ESCAPE: usize = 0x01;
     pub(crate) const STR_FUNC_EXPAND: usize = 0x02;
@@ -103,6 +105,23 @@ pub(crate) enum StrTerm {
 }
 
 impl StrTerm {
+    /// Moves byte offsets starting from `old_end` by `byte_delta`
+    /// and all line numbers by `line_delta`
+    pub(crate) fn shift(&mut self, old_end: usize, byte_delta: isize, line_delta: isize) {
+        match self {
+            Self::StringLiteral(literal) => {
+                if let Some(heredoc_end) = literal.heredoc_end.as_mut() {
+                    heredoc_end.start = shift_offset(heredoc_end.start, old_end, byte_delta);
+                    heredoc_end.end = shift_offset(heredoc_end.end, old_end, byte_delta);
+                }
+            }
+            Self::HeredocLiteral(heredoc) => {
+                heredoc.lastline = shift_line(heredoc.lastline, line_delta);
+                heredoc.sourceline = shift_line(heredoc.sourceline, line_delta);
+            }
+        }
+    }
+
     pub(crate) fn new_literal(literal: StringLiteral) -> Self {
         Self::StringLiteral(literal)
     }
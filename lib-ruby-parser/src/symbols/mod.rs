@@ -0,0 +1,485 @@
+use crate::nodes::*;
+use crate::{Loc, Node, ParserResult};
+
+/// Kind of a `DocumentSymbol`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub enum SymbolKind {
+    /// `class A`
+    Class,
+
+    /// `module A`
+    Module,
+
+    /// `def foo`, `define_method(:foo)`, `alias foo bar`, `alias_method :foo, :bar`
+    Method,
+
+    /// `def self.foo`, `def` inside of `class << self`, `define_singleton_method(:foo)`
+    SingletonMethod,
+
+    /// `A = 1`
+    Constant,
+
+    /// `attr_reader :a`, `attr_writer :a`, `attr_accessor :a`
+    Attribute,
+}
+
+/// Visibility of a `DocumentSymbol`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub enum Visibility {
+    /// Public member, the default one
+    Public,
+
+    /// Member defined after `protected` or passed to it
+    Protected,
+
+    /// Member defined after `private` or passed to it (or to `private_class_method`)
+    Private,
+
+    /// Member defined after `module_function` or passed to it
+    ModuleFunction,
+}
+
+/// A named definition with its nested definitions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentSymbol {
+    /// Name as it's written in the source, like `B::C` for `class B::C`
+    pub name: String,
+
+    /// Fully qualified name, like `A::B::C` for `class B::C` inside of `module A`.
+    ///
+    /// Instance methods and attributes are separated from their owner with `#`,
+    /// singleton methods with `.` (`A::B#foo`, `A::B.bar`).
+    pub full_name: String,
+
+    /// Kind of the symbol
+    pub kind: SymbolKind,
+
+    /// Visibility of the symbol
+    pub visibility: Visibility,
+
+    /// Location of the whole definition
+    pub loc: Loc,
+
+    /// Location of the name
+    pub name_loc: Loc,
+
+    /// Nested symbols (for classes and modules)
+    pub children: Vec<DocumentSymbol>,
+}
+
+/// Returns a nested outline of classes, modules, methods, constants
+/// and members defined by `attr_*`, `define_method` and `alias`.
+///
+/// Members of `class << self` are returned as singleton members of the enclosing
+/// class, `private`/`protected`/`module_function` sections and calls with names
+/// (like `private :foo` or `private def foo`) are taken into account.
+///
+/// # Example
+/// ```
+/// use lib_ruby_parser::symbols::{document_symbols, SymbolKind};
+/// use lib_ruby_parser::{Parser, ParserOptions};
+///
+/// let src = "module A\n  class B::C\n    def self.foo; end\n  end\nend";
+/// let result = Parser::new(src, ParserOptions::default()).do_parse();
+/// let symbols = document_symbols(&result);
+///
+/// let c = &symbols[0].children[0];
+/// assert_eq!((c.name.as_str(), c.full_name.as_str()), ("B::C", "A::B::C"));
+/// assert_eq!(c.children[0].full_name, "A::B::C.foo");
+/// assert_eq!(c.children[0].kind, SymbolKind::SingletonMethod);
+/// ```
+pub fn document_symbols(result: &ParserResult) -> Vec<DocumentSymbol> {
+    let mut indexer = Indexer {
+        frames: vec![],
+        visibility_override: None,
+    };
+    indexer.walk_namespace(String::new(), false, result.ast.as_deref())
+}
+
+#[derive(Debug)]
+struct Frame {
+    // fully qualified name of the class/module, empty on the top level
+    namespace: String,
+    // `true` inside of `class << self`
+    singleton: bool,
+    visibility: Visibility,
+    symbols: Vec<DocumentSymbol>,
+}
+
+#[derive(Debug)]
+struct Indexer {
+    frames: Vec<Frame>,
+    // visibility of a definition passed to `private`/`protected`/etc
+    visibility_override: Option<Visibility>,
+}
+
+impl Indexer {
+    fn frame(&mut self) -> &mut Frame {
+        self.frames.last_mut().expect("frame stack is empty")
+    }
+
+    fn namespace(&self) -> &str {
+        self.frames
+            .last()
+            .map(|frame| frame.namespace.as_str())
+            .unwrap_or_default()
+    }
+
+    fn qualify(&self, name: &str) -> String {
+        match self.namespace() {
+            "" => name.to_string(),
+            namespace => format!("{}::{}", namespace, name),
+        }
+    }
+
+    // Constant references are resolved lexically: `B::C` inside of `A::B`
+    // is `A::B::C`, other relative references are assumed to be nested
+    // into the current namespace.
+    fn resolve_const(&self, node: &Node) -> Option<String> {
+        let (path, absolute) = const_path(node)?;
+        if absolute {
+            return Some(path);
+        }
+        let first = path.split("::").next().unwrap_or_default();
+        let segments = self.namespace().split("::").collect::<Vec<_>>();
+        match segments.iter().rposition(|segment| *segment == first) {
+            Some(idx) => {
+                let scope = segments[..idx].join("::");
+                Some(if scope.is_empty() {
+                    path
+                } else {
+                    format!("{}::{}", scope, path)
+                })
+            }
+            None => Some(self.qualify(&path)),
+        }
+    }
+
+    // Fully qualified name of a constant defined by `class`, `module` or assignment
+    fn define_const(&self, scope: Option<&Node>, name: &str) -> String {
+        match scope {
+            None => self.qualify(name),
+            Some(Node::Cbase(_)) => name.to_string(),
+            Some(scope) => match self.resolve_const(scope) {
+                Some(scope) => format!("{}::{}", scope, name),
+                None => self.qualify(name),
+            },
+        }
+    }
+
+    fn push(&mut self, symbol: DocumentSymbol) {
+        self.frame().symbols.push(symbol)
+    }
+
+    fn push_member(
+        &mut self,
+        owner: Option<String>,
+        name: String,
+        kind: SymbolKind,
+        loc: Loc,
+        name_loc: Loc,
+    ) {
+        // bare `private`/`protected` sections don't apply to `def self.foo`
+        // (but they do apply to `def foo` inside of `class << self`)
+        let frame_visibility = if kind == SymbolKind::SingletonMethod && !self.frame().singleton {
+            Visibility::Public
+        } else {
+            self.frame().visibility
+        };
+        let singleton = kind == SymbolKind::SingletonMethod || self.frame().singleton;
+        let kind = match kind {
+            SymbolKind::Method if singleton => SymbolKind::SingletonMethod,
+            other => other,
+        };
+        let owner = owner.unwrap_or_else(|| self.namespace().to_string());
+        let full_name = match (owner.as_str(), singleton) {
+            ("", _) => name.clone(),
+            (owner, true) => format!("{}.{}", owner, name),
+            (owner, false) => format!("{}#{}", owner, name),
+        };
+        let visibility = self.visibility_override.unwrap_or(frame_visibility);
+        self.push(DocumentSymbol {
+            name,
+            full_name,
+            kind,
+            visibility,
+            loc,
+            name_loc,
+            children: vec![],
+        })
+    }
+
+    fn walk_namespace(
+        &mut self,
+        namespace: String,
+        singleton: bool,
+        body: Option<&Node>,
+    ) -> Vec<DocumentSymbol> {
+        self.frames.push(Frame {
+            namespace,
+            singleton,
+            visibility: Visibility::Public,
+            symbols: vec![],
+        });
+        let visibility_override = self.visibility_override.take();
+        if let Some(body) = body {
+            self.walk(body, true);
+        }
+        self.visibility_override = visibility_override;
+        self.frames
+            .pop()
+            .map(|frame| frame.symbols)
+            .unwrap_or_default()
+    }
+
+    fn push_namespace(&mut self, kind: SymbolKind, name: &Node, body: Option<&Node>, loc: Loc) {
+        let (written, full_name) = match name {
+            Node::Const(Const {
+                scope, name: last, ..
+            }) => {
+                let written = match const_path(name) {
+                    Some((path, _)) => path,
+                    // `class foo::Bar` can't be resolved, so it's just `Bar`
+                    None => last.clone(),
+                };
+                (written, self.define_const(scope.as_deref(), last))
+            }
+            _ => (String::new(), self.namespace().to_string()),
+        };
+        let children = self.walk_namespace(full_name.clone(), false, body);
+        self.push(DocumentSymbol {
+            name: written,
+            full_name,
+            kind,
+            visibility: Visibility::Public,
+            loc,
+            name_loc: *name.expression(),
+            children,
+        })
+    }
+
+    // `direct` is `true` for statements of a class/module body
+    // (and of the top level), only they can open visibility sections
+    fn walk(&mut self, node: &Node, direct: bool) {
+        match node {
+            Node::Begin(Begin {
+                statements,
+                begin_l: None,
+                ..
+            }) => {
+                for stmt in statements {
+                    self.walk(stmt, direct)
+                }
+            }
+            Node::Class(Class {
+                name,
+                body,
+                expression_l,
+                ..
+            }) => self.push_namespace(SymbolKind::Class, name, body.as_deref(), *expression_l),
+            Node::Module(Module {
+                name,
+                body,
+                expression_l,
+                ..
+            }) => self.push_namespace(SymbolKind::Module, name, body.as_deref(), *expression_l),
+            Node::SClass(SClass { expr, body, .. }) => {
+                let owner = self
+                    .resolve_const(expr)
+                    .unwrap_or_else(|| self.namespace().to_string());
+                let symbols = self.walk_namespace(owner, true, body.as_deref());
+                self.frame().symbols.extend(symbols);
+            }
+            Node::Def(Def {
+                name,
+                name_l,
+                expression_l,
+                ..
+            }) => self.push_member(
+                None,
+                name.clone(),
+                SymbolKind::Method,
+                *expression_l,
+                *name_l,
+            ),
+            Node::Defs(Defs {
+                definee,
+                name,
+                name_l,
+                expression_l,
+                ..
+            }) => self.push_member(
+                self.resolve_const(definee),
+                name.clone(),
+                SymbolKind::SingletonMethod,
+                *expression_l,
+                *name_l,
+            ),
+            Node::Casgn(Casgn {
+                scope,
+                name,
+                value,
+                name_l,
+                expression_l,
+                ..
+            }) => {
+                self.push(DocumentSymbol {
+                    name: name.clone(),
+                    full_name: self.define_const(scope.as_deref(), name),
+                    kind: SymbolKind::Constant,
+                    visibility: Visibility::Public,
+                    loc: *expression_l,
+                    name_loc: *name_l,
+                    children: vec![],
+                });
+                if let Some(value) = value {
+                    self.walk(value, false);
+                }
+            }
+            Node::Alias(Alias {
+                to, expression_l, ..
+            }) => {
+                if let Some(name) = literal_name(to) {
+                    self.push_member(
+                        None,
+                        name,
+                        SymbolKind::Method,
+                        *expression_l,
+                        *to.expression(),
+                    )
+                }
+            }
+            Node::Send(send) if send.recv.is_none() => self.walk_send(send, direct),
+            _ => {
+                for child in node.children() {
+                    self.walk(child, false)
+                }
+            }
+        }
+    }
+
+    fn walk_send(&mut self, send: &Send, direct: bool) {
+        let Send {
+            method_name,
+            args,
+            expression_l,
+            ..
+        } = send;
+
+        let visibility = match method_name.as_str() {
+            "public" => Some(Visibility::Public),
+            "protected" => Some(Visibility::Protected),
+            "private" => Some(Visibility::Private),
+            "module_function" => Some(Visibility::ModuleFunction),
+            _ => None,
+        };
+        if let Some(visibility) = visibility {
+            if !args.is_empty() {
+                self.apply_visibility(args, visibility, false);
+            } else if direct {
+                self.frame().visibility = visibility;
+            }
+            return;
+        }
+
+        match method_name.as_str() {
+            "private_class_method" => self.apply_visibility(args, Visibility::Private, true),
+            "public_class_method" => self.apply_visibility(args, Visibility::Public, true),
+            "attr_reader" | "attr_writer" | "attr_accessor" | "attr" => {
+                for arg in args {
+                    if let Some(name) = literal_name(arg) {
+                        self.push_member(
+                            None,
+                            name,
+                            SymbolKind::Attribute,
+                            *expression_l,
+                            *arg.expression(),
+                        )
+                    }
+                }
+            }
+            "define_method" | "define_singleton_method" | "alias_method" => {
+                let kind = if method_name == "define_singleton_method" {
+                    SymbolKind::SingletonMethod
+                } else {
+                    SymbolKind::Method
+                };
+                match args.first().and_then(|arg| Some((literal_name(arg)?, arg))) {
+                    Some((name, arg)) => {
+                        self.push_member(None, name, kind, *expression_l, *arg.expression())
+                    }
+                    None => {
+                        for arg in args {
+                            self.walk(arg, false)
+                        }
+                    }
+                }
+            }
+            _ => {
+                for arg in args {
+                    self.walk(arg, false)
+                }
+            }
+        }
+    }
+
+    // `private :foo, :bar`, `private def foo; end`, `private attr_reader :foo`
+    fn apply_visibility(&mut self, args: &[Node], visibility: Visibility, singleton: bool) {
+        let frame = self.frame();
+        // everything is a singleton member inside of `class << self`
+        let singleton = singleton || frame.singleton;
+        let frame_singleton = frame.singleton;
+
+        for arg in args {
+            match literal_name(arg) {
+                Some(name) => {
+                    let symbol = self.frame().symbols.iter_mut().rev().find(|symbol| {
+                        let symbol_singleton = match symbol.kind {
+                            SymbolKind::SingletonMethod => true,
+                            SymbolKind::Method | SymbolKind::Attribute => frame_singleton,
+                            _ => return false,
+                        };
+                        symbol_singleton == singleton && symbol.name == name
+                    });
+                    if let Some(symbol) = symbol {
+                        symbol.visibility = visibility;
+                    }
+                }
+                None => {
+                    let visibility_override = self.visibility_override.replace(visibility);
+                    self.walk(arg, false);
+                    self.visibility_override = visibility_override;
+                }
+            }
+        }
+    }
+}
+
+/// Returns `A::B` for `A::B` (and `::A::B`) and `true` if the path starts with `::`,
+/// `None` if some part of the path is not a constant
+fn const_path(node: &Node) -> Option<(String, bool)> {
+    match node {
+        Node::Const(Const { scope, name, .. }) => match scope.as_deref() {
+            None => Some((name.clone(), false)),
+            Some(Node::Cbase(_)) => Some((name.clone(), true)),
+            Some(scope) => {
+                let (scope, absolute) = const_path(scope)?;
+                Some((format!("{}::{}", scope, name), absolute))
+            }
+        },
+        _ => None,
+    }
+}
+
+fn literal_name(node: &Node) -> Option<String> {
+    match node {
+        Node::Sym(Sym { name, .. }) => Some(name.to_string_lossy()),
+        Node::Str(Str { value, .. }) => Some(value.to_string_lossy()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests;
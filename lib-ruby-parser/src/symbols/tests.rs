@@ -0,0 +1,123 @@
+use crate::symbols::{document_symbols, DocumentSymbol, SymbolKind, Visibility};
+use crate::{Loc, Parser, ParserOptions};
+
+fn flatten(src: &str) -> Vec<(String, SymbolKind, Visibility)> {
+    fn walk(symbols: &[DocumentSymbol], out: &mut Vec<(String, SymbolKind, Visibility)>) {
+        for symbol in symbols {
+            out.push((symbol.full_name.clone(), symbol.kind, symbol.visibility));
+            walk(&symbol.children, out);
+        }
+    }
+
+    let result = Parser::new(src, ParserOptions::default()).do_parse();
+    let mut out = vec![];
+    walk(&document_symbols(&result), &mut out);
+    out
+}
+
+fn public(name: &str, kind: SymbolKind) -> (String, SymbolKind, Visibility) {
+    (name.to_string(), kind, Visibility::Public)
+}
+
+fn private(name: &str, kind: SymbolKind) -> (String, SymbolKind, Visibility) {
+    (name.to_string(), kind, Visibility::Private)
+}
+
+#[test]
+fn test_nesting() {
+    let src = "module A\n  class B::C < D\n    X = 1\n    ::Y = 2\n    B::Z = 3\n  end\n  class ::E; end\nend\ndef top; end";
+
+    assert_eq!(
+        flatten(src),
+        vec![
+            public("A", SymbolKind::Module),
+            public("A::B::C", SymbolKind::Class),
+            public("A::B::C::X", SymbolKind::Constant),
+            public("Y", SymbolKind::Constant),
+            public("A::B::Z", SymbolKind::Constant),
+            public("E", SymbolKind::Class),
+            public("top", SymbolKind::Method),
+        ]
+    );
+}
+
+#[test]
+fn test_singleton_methods() {
+    let src = "class A\n  def self.a; end\n  def A.b; end\n  class << self\n    def c; end\n    attr_reader :d\n    private\n    def e; end\n  end\n  def f; end\nend";
+
+    assert_eq!(
+        flatten(src),
+        vec![
+            public("A", SymbolKind::Class),
+            public("A.a", SymbolKind::SingletonMethod),
+            public("A.b", SymbolKind::SingletonMethod),
+            public("A.c", SymbolKind::SingletonMethod),
+            public("A.d", SymbolKind::Attribute),
+            private("A.e", SymbolKind::SingletonMethod),
+            public("A#f", SymbolKind::Method),
+        ]
+    );
+}
+
+#[test]
+fn test_dsl_members_and_visibility() {
+    let src = "class A
+  attr_accessor :a, :b
+  define_method(:c) { }
+  alias d c
+  alias_method :e, :c
+  private :b
+  protected def f; end
+  private_class_method def self.g; end
+  [1].each do
+    private
+  end
+  def h; end
+  private
+  def i; end
+  def self.l; end
+  public attr_reader :j
+end
+module M
+  module_function
+  def k; end
+end";
+
+    assert_eq!(
+        flatten(src),
+        vec![
+            public("A", SymbolKind::Class),
+            public("A#a", SymbolKind::Attribute),
+            private("A#b", SymbolKind::Attribute),
+            public("A#c", SymbolKind::Method),
+            public("A#d", SymbolKind::Method),
+            public("A#e", SymbolKind::Method),
+            ("A#f".to_string(), SymbolKind::Method, Visibility::Protected),
+            private("A.g", SymbolKind::SingletonMethod),
+            public("A#h", SymbolKind::Method),
+            private("A#i", SymbolKind::Method),
+            public("A.l", SymbolKind::SingletonMethod),
+            public("A#j", SymbolKind::Attribute),
+            public("M", SymbolKind::Module),
+            (
+                "M#k".to_string(),
+                SymbolKind::Method,
+                Visibility::ModuleFunction
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_locations() {
+    let src = "class Foo\n  attr_reader :bar\nend";
+    let result = Parser::new(src, ParserOptions::default()).do_parse();
+    let symbols = document_symbols(&result);
+
+    assert_eq!(symbols[0].name, "Foo");
+    assert_eq!(symbols[0].loc, Loc { begin: 0, end: 32 });
+    assert_eq!(symbols[0].name_loc, Loc { begin: 6, end: 9 });
+    assert_eq!(symbols[0].children[0].name, "bar");
+    assert_eq!(symbols[0].children[0].loc, Loc { begin: 12, end: 28 });
+    assert_eq!(symbols[0].children[0].name_loc, Loc { begin: 24, end: 28 });
+}
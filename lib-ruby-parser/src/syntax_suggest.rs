@@ -0,0 +1,114 @@
+use crate::completeness::Completeness;
+use crate::{Loc, Parser};
+
+/// A line of the input, `end` doesn't include the trailing newline
+struct Line {
+    begin: usize,
+    end: usize,
+    /// Number of leading whitespaces, `None` for blank lines
+    indent: Option<usize>,
+}
+
+impl Line {
+    fn content<'a>(&self, input: &'a [u8]) -> &'a [u8] {
+        &input[self.begin + self.indent.unwrap_or(0)..self.end]
+    }
+
+    fn is_comment(&self, input: &[u8]) -> bool {
+        self.content(input).starts_with(b"#")
+    }
+
+    /// Returns `true` if the line starts with `end` or a closing bracket
+    fn is_closing(&self, input: &[u8]) -> bool {
+        let content = self.content(input);
+        match content.strip_prefix(b"end") {
+            Some(rest) => {
+                !matches!(rest.first(), Some(c) if c.is_ascii_alphanumeric() || *c == b'_')
+            }
+            None => matches!(content.first(), Some(b'}' | b']' | b')')),
+        }
+    }
+}
+
+fn lines(input: &[u8]) -> Vec<Line> {
+    let mut lines = vec![];
+    let mut begin = 0;
+
+    for line in input.split(|c| *c == b'\n') {
+        let end = begin + line.len();
+        let indent = line.iter().position(|c| !c.is_ascii_whitespace());
+        lines.push(Line { begin, end, indent });
+        begin = end + 1;
+    }
+
+    lines
+}
+
+/// Max number of blocks that are re-parsed while searching for an unclosed one
+const MAX_CHECKED_BLOCKS: usize = 32;
+
+/// Searches for the block that is most likely not closed,
+/// used to explain an unexpected end of input.
+///
+/// Each non-blank line starts a block that includes all following lines
+/// with a greater indentation and a closing line (`end`, `}`, etc)
+/// with the same indentation. Blocks that can't be parsed on their own
+/// because they need more code are candidates. The most indented one wins,
+/// since an unclosed block makes all blocks around it incomplete too.
+///
+/// Blocks are checked from the most indented ones, so the search stops
+/// at the first incomplete block, and gives up after `MAX_CHECKED_BLOCKS` checks.
+pub(crate) fn unclosed_block(input: &[u8]) -> Option<Loc> {
+    let lines = lines(input);
+
+    let mut blocks = vec![];
+    for (idx, line) in lines.iter().enumerate() {
+        let indent = match line.indent {
+            Some(indent) if !line.is_comment(input) => indent,
+            _ => continue,
+        };
+
+        let mut last = idx;
+        for (next_idx, next) in lines.iter().enumerate().skip(idx + 1) {
+            match next.indent {
+                None => {}
+                Some(next_indent) if next_indent > indent => last = next_idx,
+                Some(next_indent) => {
+                    if next_indent == indent && next.is_closing(input) {
+                        last = next_idx;
+                    }
+                    break;
+                }
+            }
+        }
+
+        blocks.push((indent, line, &lines[last]));
+    }
+    // stable, so blocks with the same indentation are checked top to bottom
+    blocks.sort_by(|(lhs, _, _), (rhs, _, _)| rhs.cmp(lhs));
+
+    blocks
+        .into_iter()
+        .take(MAX_CHECKED_BLOCKS)
+        .find(|(_, first, last)| {
+            matches!(
+                Parser::check_completeness(&input[first.begin..last.end]),
+                Completeness::Incomplete(_)
+            )
+        })
+        .map(|(indent, first, _)| Loc {
+            begin: first.begin + indent,
+            end: first.end,
+        })
+}
+
+#[test]
+fn test_unclosed_block() {
+    let input = b"class Foo\n  def bar\n    if x\n      1\n  end\n\n  def baz; end\nend\n";
+    assert_eq!(unclosed_block(input), Some(Loc { begin: 24, end: 28 }));
+
+    let input = b"def a\n  foo do\n    1\nend\n";
+    assert_eq!(unclosed_block(input), Some(Loc { begin: 8, end: 14 }));
+
+    assert_eq!(unclosed_block(b"def a\n  1\nend\n"), None);
+}
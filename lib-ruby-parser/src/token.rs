@@ -1,5 +1,6 @@
 use crate::parser::token_name;
-use crate::{Bytes, LexState, Loc};
+use crate::source::TriviaKind;
+use crate::{Bytes, LexState, Loc, TokenKind};
 
 /// A token that is emitted by a lexer and consumed by a parser
 #[derive(Clone, PartialEq, Eq)]
@@ -56,7 +57,22 @@ impl Token {
 
     /// Returns name of the token
     pub fn token_name(&self) -> &'static str {
-        token_name(self.token_type)
+        match TriviaKind::from_token_type(self.token_type) {
+            Some(trivia_kind) => trivia_kind.token_name(),
+            None => token_name(self.token_type),
+        }
+    }
+
+    /// Returns a typed kind of the token,
+    /// `None` for synthetic trivia tokens (see `Token::is_trivia`)
+    pub fn kind(&self) -> Option<TokenKind> {
+        TokenKind::try_from(self.token_type).ok()
+    }
+
+    /// Returns `true` if it's a synthetic trivia token,
+    /// see `ParserOptions::record_trivia`
+    pub fn is_trivia(&self) -> bool {
+        TriviaKind::from_token_type(self.token_type).is_some()
     }
 }
 
@@ -0,0 +1,113 @@
+mod token_kind_gen;
+pub use token_kind_gen::TokenKind;
+
+/// An error that is returned when converting an `i32`
+/// that doesn't match any token declared in the grammar into `TokenKind`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnknownTokenType {
+    /// Numeric value that was given
+    pub token_type: i32,
+}
+
+impl std::fmt::Display for UnknownTokenType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "UnknownTokenType: unknown token type {}",
+            self.token_type
+        )
+    }
+}
+
+impl std::error::Error for UnknownTokenType {}
+
+impl TokenKind {
+    /// Returns `true` if it's a keyword (`kDEF`, `kIF_MOD`, `k__FILE__` etc)
+    pub fn is_keyword(&self) -> bool {
+        self.name().starts_with('k')
+    }
+
+    /// Returns `true` if it's an operator:
+    /// unary, binary, ternary, assignment, range, splat or a call operator
+    pub fn is_operator(&self) -> bool {
+        matches!(
+            self,
+            Self::tPLUS
+                | Self::tMINUS
+                | Self::tSTAR
+                | Self::tSTAR2
+                | Self::tPOW
+                | Self::tDSTAR
+                | Self::tDIVIDE
+                | Self::tPERCENT
+                | Self::tAMPER
+                | Self::tAMPER2
+                | Self::tPIPE
+                | Self::tCARET
+                | Self::tTILDE
+                | Self::tBANG
+                | Self::tUPLUS
+                | Self::tUMINUS
+                | Self::tUMINUS_NUM
+                | Self::tCMP
+                | Self::tEQ
+                | Self::tEQQ
+                | Self::tNEQ
+                | Self::tGEQ
+                | Self::tLEQ
+                | Self::tGT
+                | Self::tLT
+                | Self::tANDOP
+                | Self::tOROP
+                | Self::tMATCH
+                | Self::tNMATCH
+                | Self::tDOT2
+                | Self::tDOT3
+                | Self::tBDOT2
+                | Self::tBDOT3
+                | Self::tLSHFT
+                | Self::tRSHFT
+                | Self::tANDDOT
+                | Self::tCOLON2
+                | Self::tCOLON3
+                | Self::tOP_ASGN
+                | Self::tASSOC
+                | Self::tLAMBDA
+                | Self::tEQL
+                | Self::tEH
+                | Self::tCOLON
+        )
+    }
+
+    /// Returns `true` if a literal starts with this token:
+    /// numeric and character literals, opening tokens
+    /// of strings, heredocs, symbols, regexps and word lists
+    pub fn is_literal_start(&self) -> bool {
+        matches!(
+            self,
+            Self::tINTEGER
+                | Self::tFLOAT
+                | Self::tRATIONAL
+                | Self::tIMAGINARY
+                | Self::tUMINUS_NUM
+                | Self::tCHAR
+                | Self::tSTRING_BEG
+                | Self::tXSTRING_BEG
+                | Self::tREGEXP_BEG
+                | Self::tSYMBEG
+                | Self::tWORDS_BEG
+                | Self::tQWORDS_BEG
+                | Self::tSYMBOLS_BEG
+                | Self::tQSYMBOLS_BEG
+        )
+    }
+}
+
+impl std::fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests;
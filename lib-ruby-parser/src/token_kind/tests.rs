@@ -0,0 +1,62 @@
+use crate::token_kind::{TokenKind, UnknownTokenType};
+use crate::{Lexer, Parser, ParserOptions};
+
+#[test]
+fn test_try_from_roundtrip() {
+    for kind in TokenKind::ALL {
+        assert_eq!(TokenKind::try_from(*kind as i32), Ok(*kind));
+    }
+    assert_eq!(TokenKind::try_from(Lexer::kCLASS), Ok(TokenKind::kCLASS));
+}
+
+#[test]
+fn test_try_from_unknown() {
+    assert_eq!(
+        TokenKind::try_from(-42),
+        Err(UnknownTokenType { token_type: -42 })
+    );
+    assert_eq!(TokenKind::try_from(Lexer::tWHITESPACE).ok(), None);
+}
+
+#[test]
+fn test_display() {
+    assert_eq!(TokenKind::kDEF.to_string(), "kDEF");
+    assert_eq!(TokenKind::tSTRING_BEG.to_string(), "tSTRING_BEG");
+    assert_eq!(TokenKind::END_OF_INPUT.to_string(), "END_OF_INPUT");
+}
+
+#[test]
+fn test_categories() {
+    assert!(TokenKind::kIF_MOD.is_keyword());
+    assert!(!TokenKind::tIDENTIFIER.is_keyword());
+
+    assert!(TokenKind::tPLUS.is_operator());
+    assert!(TokenKind::tOP_ASGN.is_operator());
+    assert!(!TokenKind::tLPAREN.is_operator());
+
+    assert!(TokenKind::tINTEGER.is_literal_start());
+    assert!(TokenKind::tSTRING_BEG.is_literal_start());
+    assert!(!TokenKind::tSTRING_END.is_literal_start());
+}
+
+#[test]
+fn test_token_kind() {
+    let result = Parser::new("def foo; 42; end", ParserOptions::default()).do_parse();
+
+    assert_eq!(
+        result
+            .tokens
+            .iter()
+            .map(|token| token.kind())
+            .collect::<Vec<_>>(),
+        vec![
+            Some(TokenKind::kDEF),
+            Some(TokenKind::tIDENTIFIER),
+            Some(TokenKind::tSEMI),
+            Some(TokenKind::tINTEGER),
+            Some(TokenKind::tSEMI),
+            Some(TokenKind::kEND),
+            Some(TokenKind::END_OF_INPUT),
+        ]
+    );
+}
@@ -0,0 +1,73 @@
+use crate::{LexState, Token};
+
+/// Enum of all events that are reported to a `Tracer`
+#[derive(Debug)]
+pub enum TraceEvent<'a> {
+    /// Lexer has produced a token
+    TokenLexed {
+        /// Produced token
+        token: &'a Token,
+    },
+
+    /// State of the lexer has changed,
+    /// reported once per token (or when `TokenRewriter` changes it)
+    LexStateChanged {
+        /// State before the change
+        before: LexState,
+
+        /// State after the change
+        after: LexState,
+    },
+
+    /// Parser has shifted a token onto its stack
+    Shift {
+        /// Shifted token
+        token: &'a Token,
+    },
+
+    /// Parser is about to reduce a grammar rule
+    Reduce {
+        /// Grammar rule, like `"arg: arg tPLUS arg"`.
+        ///
+        /// Empty rules are rendered as `"opt_terms: %empty"`,
+        /// mid-rule actions as `"$@1: %empty"`
+        rule: &'a str,
+    },
+
+    /// Flag of the parser context has changed
+    ContextFlagChanged {
+        /// Name of the flag, like `"in_def"`
+        flag: &'static str,
+
+        /// New value of the flag
+        value: bool,
+    },
+}
+
+/// Tracer function
+pub type TracerFn = dyn Fn(&TraceEvent);
+
+/// Tracer struct, receives structured events from the lexer and the parser.
+///
+/// When it's not set (that's the default) no events are constructed.
+pub struct Tracer {
+    f: Box<TracerFn>,
+}
+
+impl Tracer {
+    /// Constructs a tracer based on a given function
+    pub fn new(f: Box<TracerFn>) -> Self {
+        Self { f }
+    }
+
+    pub(crate) fn call(&self, event: &TraceEvent) {
+        let f = &*self.f;
+        f(event)
+    }
+}
+
+impl std::fmt::Debug for Tracer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Tracer").finish()
+    }
+}
@@ -13,6 +13,10 @@ pub struct Finder {
 
 mod finder_gen;
 
+mod node_path;
+mod node_path_gen;
+pub use node_path::{NodePath, NodePathItem};
+
 impl Finder {
     /// Performs a search of a given pattern on a given AST.
     ///
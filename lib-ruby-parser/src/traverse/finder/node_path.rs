@@ -0,0 +1,117 @@
+use crate::traverse::finder::{Pattern, PatternItem};
+use crate::Node;
+
+/// An ancestor of a node found by `Node::path_to`
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodePathItem<'a> {
+    /// Ancestor node
+    pub node: &'a Node,
+
+    /// Transition from `node` into the next node of the path,
+    /// `[Idx]`-terminated if the next node is an element of a `Vec<Node>` field
+    pub edge: Vec<PatternItem>,
+}
+
+/// Innermost node that covers a given offset, together with its ancestors
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodePath<'a> {
+    /// Ancestors of `node`, starting from the root
+    pub ancestors: Vec<NodePathItem<'a>>,
+
+    /// Innermost node that covers a given offset
+    pub node: &'a Node,
+}
+
+impl NodePath<'_> {
+    /// Returns a `Pattern` that leads from the root to `node`,
+    /// can be passed to `Finder`
+    pub fn pattern(&self) -> Pattern {
+        let mut pattern = Pattern::empty();
+        pattern.push(PatternItem::Root);
+        for ancestor in self.ancestors.iter() {
+            for item in ancestor.edge.iter() {
+                pattern.push(*item);
+            }
+        }
+        pattern
+    }
+}
+
+impl Node {
+    /// Returns the innermost node that covers a given byte offset.
+    ///
+    /// A node covers offsets from `expression().begin` (inclusive)
+    /// to `expression().end` (exclusive).
+    pub fn node_at(&self, offset: usize) -> Option<&Node> {
+        self.path_to(offset).map(|path| path.node)
+    }
+
+    /// Returns the innermost node that covers a given byte offset
+    /// together with all its ancestors starting from `self`.
+    pub fn path_to(&self, offset: usize) -> Option<NodePath<'_>> {
+        let mut ancestors = vec![];
+        let node = innermost(self, offset, &mut ancestors)?;
+        Some(NodePath { ancestors, node })
+    }
+}
+
+fn covers(node: &Node, offset: usize) -> bool {
+    let loc = node.expression();
+    loc.begin <= offset && offset < loc.end
+}
+
+fn heredoc_body_covers(node: &Node, offset: usize) -> bool {
+    let loc = match node {
+        Node::Heredoc(heredoc) => heredoc.heredoc_body_l,
+        Node::XHeredoc(heredoc) => heredoc.heredoc_body_l,
+        _ => return false,
+    };
+    loc.begin <= offset && offset < loc.end
+}
+
+// Returns `true` if there's a heredoc with a body that covers `offset`
+// somewhere in the subtree of `node`
+fn has_heredoc_body_at(node: &Node, offset: usize) -> bool {
+    if heredoc_body_covers(node, offset) {
+        return true;
+    }
+    node.children_with_edges()
+        .into_iter()
+        .any(|(_, child)| child.expression().begin <= offset && has_heredoc_body_at(child, offset))
+}
+
+// Only children that cover `offset` are searched, except for the case
+// when none of them does: `offset` can still be in a body of a heredoc
+// that is located outside of the expression of its node (and all its ancestors),
+// but after it, so then preceding children are searched for such heredoc
+fn innermost<'a>(
+    node: &'a Node,
+    offset: usize,
+    ancestors: &mut Vec<NodePathItem<'a>>,
+) -> Option<&'a Node> {
+    let children = node.children_with_edges();
+
+    let covering = children
+        .iter()
+        .position(|(_, child)| covers(child, offset))
+        .or_else(|| {
+            children.iter().rposition(|(_, child)| {
+                child.expression().begin <= offset && has_heredoc_body_at(child, offset)
+            })
+        });
+
+    if let Some(idx) = covering {
+        let (edge, child) = children.into_iter().nth(idx).unwrap();
+        ancestors.push(NodePathItem { node, edge });
+        if let Some(found) = innermost(child, offset, ancestors) {
+            return Some(found);
+        }
+        ancestors.pop();
+    }
+
+    if covers(node, offset) {
+        Some(node)
+    } else {
+        None
+    }
+}
@@ -1,4 +1,4 @@
-use crate::traverse::finder::Finder;
+use crate::traverse::finder::{Finder, Pattern};
 use crate::{Parser, ParserOptions};
 
 fn find(src: &str, pattern: &str) -> Option<String> {
@@ -30,3 +30,55 @@ fn it_returns_none_if_no_node() {
 
     assert_eq!(None, find(src, pattern))
 }
+
+#[test]
+fn it_finds_innermost_node_at_offset() {
+    let options = ParserOptions {
+        buffer_name: "(find_test)".into(),
+        ..Default::default()
+    };
+    let result = Parser::new("foo(1 + bar)", options).do_parse();
+    let ast = result.ast.as_ref().expect("expected AST to be Some");
+    let source_at = |offset: usize| {
+        ast.node_at(offset)
+            .and_then(|node| node.expression().source(&result.input))
+    };
+
+    assert_eq!(source_at(0), Some("foo(1 + bar)".to_string()));
+    assert_eq!(source_at(4), Some("1".to_string()));
+    assert_eq!(source_at(6), Some("1 + bar".to_string()));
+    assert_eq!(source_at(9), Some("bar".to_string()));
+    assert_eq!(source_at(12), None);
+
+    let path = ast.path_to(9).unwrap();
+    let ancestors = path
+        .ancestors
+        .iter()
+        .map(|ancestor| ancestor.node.expression().source(&result.input).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(ancestors, vec!["foo(1 + bar)", "1 + bar"]);
+
+    let pattern = path.pattern();
+    assert_eq!(
+        pattern,
+        Pattern::new("root -> args -> 0 -> args -> 0").unwrap()
+    );
+    assert_eq!(
+        Finder::run("root -> args -> 0 -> args -> 0", ast)
+            .unwrap()
+            .as_ref(),
+        Some(path.node)
+    );
+}
+
+#[test]
+fn it_finds_node_inside_heredoc_body() {
+    let src = "foo(<<~E)\n  body\nE\n";
+    let result = Parser::new(src, ParserOptions::default()).do_parse();
+    let ast = result.ast.as_ref().expect("expected AST to be Some");
+
+    let path = ast.path_to(src.find("body").unwrap()).unwrap();
+    let source = path.node.expression().source(&result.input).unwrap();
+    assert!(source.contains("body"));
+    assert_eq!(path.ancestors.first().map(|item| item.node), Some(ast));
+}
@@ -1,10 +1,12 @@
 use std::cell::RefCell;
-use std::collections::BTreeSet;
+use std::collections::BTreeMap;
 use std::rc::Rc;
 
+use crate::Loc;
+
 #[derive(Debug, Clone, Default)]
 pub(crate) struct VariablesStack {
-    stack: Rc<RefCell<Vec<BTreeSet<String>>>>,
+    stack: Rc<RefCell<Vec<BTreeMap<String, Loc>>>>,
 }
 
 impl VariablesStack {
@@ -19,26 +21,27 @@ impl VariablesStack {
     }
 
     pub(crate) fn push(&self) {
-        self.stack.borrow_mut().push(BTreeSet::new())
+        self.stack.borrow_mut().push(BTreeMap::new())
     }
 
     pub(crate) fn pop(&self) {
         self.stack.borrow_mut().pop();
     }
 
-    pub(crate) fn declare(&self, name: &str) {
+    pub(crate) fn declare(&self, name: &str, loc: &Loc) {
         self.stack
             .borrow_mut()
             .last_mut()
             .expect("expected variables_stack to have at least 1 layer")
-            .insert(name.to_string());
+            .insert(name.to_string(), *loc);
     }
 
-    pub(crate) fn is_declared(&self, name: &str) -> bool {
+    pub(crate) fn declared_at(&self, name: &str) -> Option<Loc> {
         self.stack
             .borrow()
             .last()
             .expect("expected variables_stack to have at least 1 layer")
-            .contains(name)
+            .get(name)
+            .copied()
     }
 }
@@ -1,9 +1,12 @@
 use super::InputFile;
-use lib_ruby_parser::{Lexer, Token};
+use lib_ruby_parser::{Lexer, ParserOptions, Token};
 
 pub fn tokenize(input: InputFile) -> Result<Vec<Token>, String> {
     print!("tokenizing {} ... ", input.filepath);
-    let mut lexer = Lexer::new(input.code, input.filepath, None);
-    let tokens = lexer.tokenize_until_eof();
+    let options = ParserOptions {
+        buffer_name: input.filepath,
+        ..Default::default()
+    };
+    let tokens = Lexer::tokenize(input.code, options);
     Ok(tokens)
 }
@@ -8,6 +8,7 @@ enum TestSection {
     Locations,
     Diagnostic,
     DependsOnFeature,
+    Options,
 }
 
 #[derive(Debug)]
@@ -17,6 +18,7 @@ struct Fixture {
     locs: Option<Vec<String>>,
     diagnostics: Option<Vec<String>>,
     depends_on_features: Option<Vec<String>>,
+    options: Option<Vec<String>>,
 }
 
 fn none_if_empty<T: PartialEq<&'static str>>(v: Vec<T>) -> Option<Vec<T>> {
@@ -37,6 +39,7 @@ impl Fixture {
         let mut locs: Vec<String> = vec![];
         let mut diagnostics: Vec<String> = vec![];
         let mut depends_on_features: Vec<String> = vec![];
+        let mut options: Vec<String> = vec![];
         let mut current_section = TestSection::None;
 
         for line in content.lines() {
@@ -48,12 +51,14 @@ impl Fixture {
                 (b"--LOCATIONS", _) => current_section = TestSection::Locations,
                 (b"--DIAGNOSTIC", _) => current_section = TestSection::Diagnostic,
                 (b"--DEPENDS-ON-FEATURES", _) => current_section = TestSection::DependsOnFeature,
+                (b"--OPTIONS", _) => current_section = TestSection::Options,
 
                 (_, &TestSection::Input) => input.push(line.to_string()),
                 (_, &TestSection::Ast) => ast.push(line.to_string()),
                 (_, &TestSection::Locations) => locs.push(line.to_string()),
                 (_, &TestSection::Diagnostic) => diagnostics.push(line.to_string()),
                 (_, &TestSection::DependsOnFeature) => depends_on_features.push(line.to_string()),
+                (_, &TestSection::Options) => options.push(line.to_string()),
 
                 (_, &TestSection::None) => {
                     panic!("empty state while parsing fixture on line {:#?}", line)
@@ -66,6 +71,7 @@ impl Fixture {
         let locs = none_if_empty(locs);
         let diagnostics = none_if_empty(diagnostics);
         let depends_on_features = none_if_empty(depends_on_features);
+        let options = none_if_empty(options);
 
         if let (None, None, None) = (&ast, &locs, &diagnostics) {
             panic!("empty test")
@@ -77,6 +83,7 @@ impl Fixture {
             locs,
             diagnostics,
             depends_on_features,
+            options,
         }
     }
 
@@ -166,11 +173,18 @@ pub(crate) fn test_file(fixture_path: &str) {
         }
     }
 
-    let options = ParserOptions {
+    let mut options = ParserOptions {
         buffer_name: format!("(test {})", fixture_path),
         record_tokens: false,
         ..Default::default()
     };
+    for option in fixture.options.iter().flatten() {
+        match &option[..] {
+            "verbose" => options.verbose = true,
+            "warn_indent" => options.warn_indent = true,
+            unsupported => panic!("Unsupported option {:?}", unsupported),
+        }
+    }
     let parser = Parser::new(fixture.input.as_bytes(), options);
 
     parser.static_env.declare("foo");
@@ -91,3 +91,23 @@ fixture_file!("fixtures/parser/manual", test_unterminated_embedded_doc_0);
 fixture_file!("fixtures/parser/manual", test_unterminated_embedded_doc_1);
 fixture_file!("fixtures/parser/manual", case_with_multipl_whens_and_else);
 fixture_file!("fixtures/parser/manual", casematch_with_multiple_ins_and_else);
+fixture_file!("fixtures/parser/manual", suppression_comment_end_of_line);
+fixture_file!("fixtures/parser/manual", suppression_comment_disable_enable);
+fixture_file!("fixtures/parser/manual", suppression_comment_unused);
+fixture_file!("fixtures/parser/manual", void_context_operator);
+fixture_file!("fixtures/parser/manual", void_context_variable);
+fixture_file!("fixtures/parser/manual", unused_literal_ignored);
+fixture_file!("fixtures/parser/manual", literal_assignment_in_condition);
+fixture_file!("fixtures/parser/manual", literal_in_condition);
+fixture_file!("fixtures/parser/manual", literal_in_condition_non_verbose);
+fixture_file!("fixtures/parser/manual", duplicated_when_clause);
+fixture_file!("fixtures/parser/manual", unreachable_in_clause);
+fixture_file!(
+    "fixtures/parser/manual",
+    mismatched_indentation_magic_comment
+);
+fixture_file!("fixtures/parser/manual", mismatched_indentation_option);
+fixture_file!(
+    "fixtures/parser/manual",
+    mismatched_indentation_keyword_aligned
+);
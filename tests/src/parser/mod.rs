@@ -7,9 +7,15 @@ mod gen;
 mod manual;
 
 use lib_ruby_parser::{
+    source::token_rewriter::{
+        LexStateAction, RewriteAction, TokenRewriter, TokenRewriterContext, TokenRewriterResult,
+    },
     source::{MagicComment, MagicCommentKind},
-    Loc, Parser, ParserOptions, ParserResult,
+    Bytes, Diagnostic, DiagnosticAction, DiagnosticHandler, InputEdit, Lexer, LineTokens, Loc,
+    Parser, ParserOptions, ParserResult, Token, TraceEvent, Tracer,
 };
+use std::cell::RefCell;
+use std::rc::Rc;
 
 macro_rules! fixture_file {
     ($dir:literal, $fixture:ident) => {
@@ -73,3 +79,418 @@ fn test_magic_comment() {
         ]
     );
 }
+
+#[test]
+fn test_diagnostic_related_locations() {
+    fn related_of(input: &[u8]) -> Vec<(Loc, String)> {
+        let ParserResult { diagnostics, .. } = parse(input);
+        assert_eq!(diagnostics.len(), 1, "expected 1 diagnostic");
+        diagnostics.into_iter().next().unwrap().related
+    }
+
+    let previously_defined = || "previously defined here".to_string();
+
+    assert_eq!(
+        related_of(b"def m(a, a); end"),
+        vec![(Loc { begin: 6, end: 7 }, previously_defined())]
+    );
+    assert_eq!(
+        related_of(b"{ a: 1, a: 2 }"),
+        vec![(Loc { begin: 2, end: 3 }, previously_defined())]
+    );
+    assert_eq!(
+        related_of(b"case 1; in [a, a]; end"),
+        vec![(Loc { begin: 12, end: 13 }, previously_defined())]
+    );
+    assert_eq!(
+        related_of(b"case 1; in {a: 1, a: 2}; end"),
+        vec![(Loc { begin: 12, end: 14 }, previously_defined())]
+    );
+    assert_eq!(
+        related_of(b"def m(a = a); end"),
+        vec![(
+            Loc { begin: 6, end: 7 },
+            "argument is defined here".to_string()
+        )]
+    );
+}
+
+#[test]
+fn test_case_warnings_related_locations() {
+    fn related_of(input: &[u8]) -> Vec<(Loc, Vec<(Loc, String)>)> {
+        let options = ParserOptions {
+            verbose: true,
+            ..Default::default()
+        };
+        Parser::new(input, options)
+            .do_parse()
+            .diagnostics
+            .into_iter()
+            .map(|diagnostic| (diagnostic.loc, diagnostic.related))
+            .collect()
+    }
+
+    assert_eq!(
+        related_of(b"case foo\nwhen 1, :a\nwhen :a then 2\nend"),
+        vec![(
+            Loc { begin: 25, end: 27 },
+            vec![(
+                Loc { begin: 17, end: 19 },
+                "previously defined here".to_string()
+            )]
+        )]
+    );
+    assert_eq!(
+        related_of(b"case 1\nin x then x\nin 2\nend"),
+        vec![(
+            Loc { begin: 22, end: 23 },
+            vec![(
+                Loc { begin: 10, end: 11 },
+                "this pattern matches any value".to_string()
+            )]
+        )]
+    );
+}
+
+#[test]
+fn test_on_diagnostic() {
+    let input = b"def m(a, a); end; def m(b, b); end";
+
+    let streamed: Rc<RefCell<Vec<Diagnostic>>> = Rc::new(RefCell::new(vec![]));
+    let handler = {
+        let streamed = Rc::clone(&streamed);
+        DiagnosticHandler::new(Box::new(move |diagnostic| {
+            streamed.borrow_mut().push(diagnostic.clone());
+            DiagnosticAction::Continue
+        }))
+    };
+    let options = ParserOptions {
+        on_diagnostic: Some(handler),
+        ..Default::default()
+    };
+    let result = Parser::new(&input[..], options).do_parse();
+
+    assert!(!result.aborted);
+    assert!(result.ast.is_some());
+    assert_eq!(result.diagnostics.len(), 2);
+    assert_eq!(*streamed.borrow(), result.diagnostics);
+
+    let handler = DiagnosticHandler::new(Box::new(|_| DiagnosticAction::Stop));
+    let options = ParserOptions {
+        on_diagnostic: Some(handler),
+        ..Default::default()
+    };
+    let result = Parser::new(&input[..], options).do_parse();
+
+    assert!(result.aborted);
+    assert!(result.ast.is_none());
+    assert_eq!(result.diagnostics.len(), 1);
+}
+
+#[test]
+fn test_tracer() {
+    let events: Rc<RefCell<Vec<String>>> = Rc::new(RefCell::new(vec![]));
+    let tracer = {
+        let events = Rc::clone(&events);
+        Tracer::new(Box::new(move |event| {
+            let event = match event {
+                TraceEvent::TokenLexed { token } => format!("lexed {}", token.token_name()),
+                TraceEvent::Shift { token } => format!("shift {}", token.token_name()),
+                TraceEvent::Reduce { rule } => format!("reduce {}", rule),
+                TraceEvent::ContextFlagChanged { flag, value } => format!("{} = {}", flag, value),
+                TraceEvent::LexStateChanged { .. } => return,
+            };
+            events.borrow_mut().push(event);
+        }))
+    };
+    let options = ParserOptions {
+        tracer: Some(tracer),
+        ..Default::default()
+    };
+    let result = Parser::new(b"def m; end".to_vec(), options).do_parse();
+    assert!(result.ast.is_some());
+
+    let events = events.borrow();
+    let position = |event: &str| {
+        events
+            .iter()
+            .position(|e| e == event)
+            .unwrap_or_else(|| panic!("no {:?} event in {:#?}", event, events))
+    };
+
+    assert!(position("lexed kDEF") < position("shift kDEF"));
+    assert!(position("in_def = true") < position("in_def = false"));
+    assert!(position("shift kEND") < position("reduce k_end: kEND"));
+    assert_eq!(
+        events.last().map(|e| e.as_str()),
+        Some("reduce program: $@1 top_compstmt")
+    );
+}
+
+#[test]
+fn test_record_trivia() {
+    let input = "=begin\ndoc\n=end\nfoo(1,  # comment\n    2) \\\n  .bar\n\n__END__\ndata";
+    let options = ParserOptions {
+        record_trivia: true,
+        ..Default::default()
+    };
+    let result = Parser::new(input.as_bytes().to_vec(), options).do_parse();
+
+    let mut pos = 0;
+    let mut source = String::new();
+    for token in result
+        .tokens
+        .iter()
+        .filter(|token| token.token_type != Lexer::END_OF_INPUT)
+    {
+        assert_eq!(token.loc.begin, pos, "gap or overlap before {:?}", token);
+        pos = token.loc.end;
+        source.push_str(&token.loc.source(&result.input).unwrap());
+    }
+    assert_eq!(source, input);
+
+    let trivia_names = result
+        .tokens
+        .iter()
+        .filter(|token| token.is_trivia())
+        .map(|token| token.token_name())
+        .collect::<Vec<_>>();
+    assert!(trivia_names.contains(&"tEMBDOC"));
+    assert!(trivia_names.contains(&"tCOMMENT"));
+    assert!(trivia_names.contains(&"tLINE_CONTINUATION"));
+    assert!(trivia_names.contains(&"tIGNORED_NL"));
+    assert_eq!(trivia_names.last(), Some(&"tEND_OF_DATA"));
+
+    let options = ParserOptions {
+        record_trivia: false,
+        ..Default::default()
+    };
+    let without_trivia = Parser::new(input.as_bytes().to_vec(), options).do_parse();
+    assert!(without_trivia.tokens.iter().all(|token| !token.is_trivia()));
+    assert_eq!(without_trivia.ast, result.ast);
+}
+
+#[test]
+fn test_token_rewriter_replace() {
+    let in_def = Rc::new(RefCell::new(vec![]));
+    let in_def_log = Rc::clone(&in_def);
+
+    // expands `foo` into `bar + baz`
+    let rewrite = move |token: Box<Token>, context: &TokenRewriterContext| {
+        let token_action = if token.to_string_lossy() == "foo" {
+            in_def_log.borrow_mut().push(context.context.in_def);
+
+            let make_token = |token_type: i32, value: &str| Token {
+                token_type,
+                token_value: Bytes::new(value.as_bytes().to_vec()),
+                ..(*token).clone()
+            };
+            RewriteAction::Replace(vec![
+                make_token(Lexer::tIDENTIFIER, "bar"),
+                make_token(Lexer::tPLUS, "+"),
+                make_token(Lexer::tIDENTIFIER, "baz"),
+            ])
+        } else {
+            RewriteAction::Keep
+        };
+
+        TokenRewriterResult {
+            rewritten_token: token,
+            token_action,
+            lex_state_action: LexStateAction::Keep,
+        }
+    };
+    let options = ParserOptions {
+        token_rewriter: Some(TokenRewriter::with_context(Box::new(rewrite))),
+        ..Default::default()
+    };
+    let result = Parser::new(b"foo; def m; foo; end".to_vec(), options).do_parse();
+
+    assert_eq!(*in_def.borrow(), vec![false, true]);
+    assert_eq!(
+        result
+            .tokens
+            .iter()
+            .map(|token| token.to_string_lossy())
+            .collect::<Vec<_>>(),
+        vec!["bar", "+", "baz", ";", "def", "m", ";", "bar", "+", "baz", ";", "end", ""]
+    );
+    assert!(result.diagnostics.is_empty());
+}
+
+#[test]
+fn test_lexer_tokenize() {
+    let input = b"a ? b : c\nfoo = 1; foo /x/ 2\nbar /x/\n".to_vec();
+
+    let tokens = Lexer::tokenize(input.clone(), ParserOptions::default());
+    let result = Parser::new(input, ParserOptions::default()).do_parse();
+
+    assert_eq!(tokens, result.tokens);
+
+    let token_types = tokens
+        .iter()
+        .map(|token| token.token_type)
+        .collect::<Vec<_>>();
+    assert!(token_types.contains(&Lexer::tEH));
+    assert!(token_types.contains(&Lexer::tDIVIDE));
+    assert!(token_types.contains(&Lexer::tREGEXP_BEG));
+}
+
+fn relex(
+    old_input: &[u8],
+    old: LineTokens,
+    edit: InputEdit,
+    replacement: &[u8],
+) -> (Vec<u8>, LineTokens) {
+    let mut new_input = old_input.to_vec();
+    new_input.splice(edit.start..edit.old_end, replacement.iter().cloned());
+
+    let relexed = Lexer::new(new_input.clone(), "(eval)", None).relex(&old.snapshots, edit);
+    let spliced = relexed.splice(old);
+
+    let expected = Lexer::new(new_input.clone(), "(eval)", None).tokenize_lines();
+    assert_eq!(spliced.tokens, expected.tokens);
+
+    (new_input, spliced)
+}
+
+#[test]
+fn test_lexer_relex() {
+    let inputs: &[&[u8]] = &[
+        b"a = 1\nb = 2\nc = 3\n",
+        b"foo(\n  1,\n  2\n)\nbar\n",
+        b"x = <<~HERE\n  text\n  more\nHERE\ny = \"a\n b\"\nz\n",
+        b"def m(a)\n  a /2/ 3\nend\nm 1\n",
+    ];
+
+    for input in inputs {
+        for start in 0..input.len() {
+            for (old_end, replacement) in [
+                (start, &b"x"[..]),
+                (start, &b"\n"[..]),
+                (start + 1, &b""[..]),
+                (start + 1, &b"42"[..]),
+            ] {
+                let old = Lexer::new(input.to_vec(), "(eval)", None).tokenize_lines();
+                let edit = InputEdit {
+                    start,
+                    old_end,
+                    new_end: start + replacement.len(),
+                };
+                let (new_input, spliced) = relex(input, old, edit, replacement);
+
+                // shifted snapshots can be re-used for the next edit
+                let end = new_input.len();
+                let edit = InputEdit {
+                    start: end,
+                    old_end: end,
+                    new_end: end + 2,
+                };
+                relex(&new_input, spliced, edit, b"1\n");
+            }
+        }
+    }
+}
+
+#[test]
+fn test_parser_checkpoint() {
+    let prefixes = [
+        "def foo\n  bar(1,\n",
+        "a = 1\nif a\n  b = <<~X\n    text\n  X\n",
+        "# frozen_string_literal: true\nfoo do |x|\n  x.each { |y| # comment\n",
+        "foo = 1\nfoo /x/\n",
+    ];
+    let continuations = ["", "end\n", "}\nend\n", "2)\nend\n", "x/\n", "foo\nend"];
+
+    for prefix in prefixes {
+        let checkpoint = Parser::new(prefix, ParserOptions::default()).checkpoint();
+        assert_eq!(checkpoint.prefix(), prefix.as_bytes());
+        assert!(checkpoint.consumed_len() <= prefix.len());
+
+        for continuation in continuations {
+            let forked = checkpoint
+                .fork(continuation, ParserOptions::default())
+                .do_parse();
+            let expected = Parser::new(
+                format!("{}{}", prefix, continuation),
+                ParserOptions::default(),
+            )
+            .do_parse();
+
+            assert_eq!(forked.ast, expected.ast);
+            assert_eq!(forked.tokens, expected.tokens);
+            assert_eq!(forked.diagnostics, expected.diagnostics);
+            assert_eq!(forked.comments, expected.comments);
+            assert_eq!(forked.magic_comments, expected.magic_comments);
+        }
+    }
+}
+
+#[test]
+fn test_unclosed_block_suggestion() {
+    let input = "def foo\n  [1, 2].each do |x|\n    puts x\n\n  bar\nend\n";
+    let options = ParserOptions {
+        suggest_unclosed_block: true,
+        ..Default::default()
+    };
+    let result = Parser::new(input, options).do_parse();
+
+    assert_eq!(result.diagnostics.len(), 1);
+    let diagnostic = &result.diagnostics[0];
+    assert_eq!(diagnostic.render_message(), "unexpected END_OF_INPUT");
+    assert_eq!(
+        diagnostic.related,
+        vec![(
+            Loc { begin: 10, end: 29 },
+            String::from("this block is probably not closed")
+        )]
+    );
+}
+
+#[test]
+fn test_unclosed_block_suggestion_is_opt_in() {
+    let input = "def foo\n  [1, 2].each do |x|\n    puts x\n\n  bar\nend\n";
+    let result = Parser::new(input, ParserOptions::default()).do_parse();
+
+    assert_eq!(result.diagnostics.len(), 1);
+    assert_eq!(result.diagnostics[0].related, vec![]);
+}
+
+#[test]
+fn test_assigned_but_unused_variable() {
+    fn unused_of(input: &str) -> Vec<String> {
+        let options = ParserOptions {
+            verbose: true,
+            ..Default::default()
+        };
+        Parser::new(input, options)
+            .do_parse()
+            .diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.render_message())
+            .collect()
+    }
+
+    assert_eq!(
+        unused_of("def m(a); b = a; _c = 1; end"),
+        vec![String::from("assigned but unused variable - b")]
+    );
+    assert_eq!(
+        unused_of("x = 1; [1].each { |y| z = y; x += 1 }"),
+        vec![String::from("assigned but unused variable - z")]
+    );
+    assert_eq!(
+        unused_of("case 1; in [a, b] then a; end"),
+        vec![String::from("assigned but unused variable - b")]
+    );
+    assert_eq!(
+        unused_of("def m; a = 1; binding; end"),
+        Vec::<String>::new()
+    );
+    assert_eq!(
+        Parser::new("a = 1", ParserOptions::default())
+            .do_parse()
+            .diagnostics,
+        vec![]
+    );
+}